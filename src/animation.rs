@@ -0,0 +1,239 @@
+use crate::{
+    camera::camera::Camera,
+    objects::{hittable::Hittable, transform::Transform},
+    vec3::{Point3, Vec3},
+};
+use std::{
+    ops::{Add, Mul, Sub},
+    rc::Rc,
+};
+
+// Values that can be interpolated between keyframes.
+pub trait Animatable:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f64, Output = Self>
+{
+}
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>> Animatable for T {}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    // A cubic through every keyframe, with the tangent at each key taken from its neighbours.
+    CatmullRom,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Keyframe<T> {
+    pub time: f64,
+    pub value: T,
+}
+
+// A value over time. Before the first and after the last keyframe the track holds its end
+// values.
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    pub keys: Vec<Keyframe<T>>,
+    pub interpolation: Interpolation,
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            keys: vec![],
+            interpolation,
+        }
+    }
+
+    pub fn constant(value: T) -> Self {
+        let mut track = Self::new(Interpolation::Linear);
+        track.add(0., value);
+        track
+    }
+
+    pub fn add(&mut self, time: f64, value: T) {
+        let index = self.keys.iter().take_while(|k| k.time <= time).count();
+        self.keys.insert(index, Keyframe { time, value });
+    }
+
+    pub fn with_key(mut self, time: f64, value: T) -> Self {
+        self.add(time, value);
+        self
+    }
+
+    pub fn sample(&self, time: f64) -> T {
+        let keys = &self.keys;
+        assert!(!keys.is_empty(), "Cannot sample an empty track");
+
+        // A time that isn't a number falls before every key rather than between two of them.
+        let last = keys.len() - 1;
+        if time.is_nan() || time <= keys[0].time {
+            return keys[0].value;
+        }
+        if time >= keys[last].time {
+            return keys[last].value;
+        }
+
+        let i = keys.iter().take_while(|k| k.time <= time).count() - 1;
+        let (k1, k2) = (keys[i], keys[i + 1]);
+        let span = k2.time - k1.time;
+        let u = (time - k1.time) / span;
+
+        match self.interpolation {
+            Interpolation::Linear => k1.value + (k2.value - k1.value) * u,
+            Interpolation::CatmullRom => {
+                let m1 = self.tangent(i) * span;
+                let m2 = self.tangent(i + 1) * span;
+                let (u2, u3) = (u * u, u * u * u);
+
+                k1.value * (2. * u3 - 3. * u2 + 1.)
+                    + m1 * (u3 - 2. * u2 + u)
+                    + k2.value * (-2. * u3 + 3. * u2)
+                    + m2 * (u3 - u2)
+            }
+        }
+    }
+
+    // Rate of change at key `i`, from the neighbouring keys or the single neighbour at the ends.
+    fn tangent(&self, i: usize) -> T {
+        let keys = &self.keys;
+        let previous = keys[i.saturating_sub(1)];
+        let next = keys[(i + 1).min(keys.len() - 1)];
+        (next.value - previous.value) * (1. / (next.time - previous.time))
+    }
+}
+
+// Keyframed camera placement and lens settings. The focus distance follows the distance to the
+// look-at point.
+pub struct CameraAnimation {
+    pub position: Track<Point3>,
+    pub look_at: Track<Point3>,
+    pub fov: Track<f64>,
+    pub aperture: Track<f64>,
+}
+
+impl CameraAnimation {
+    pub fn camera_at(&self, time: f64, v_up: Vec3, aspect_ratio: f64) -> Camera {
+        let look_from = self.position.sample(time);
+        let look_at = self.look_at.sample(time);
+        Camera::from(
+            look_from,
+            look_at,
+            v_up,
+            self.fov.sample(time),
+            aspect_ratio,
+            self.aperture.sample(time),
+            (look_from - look_at).length(),
+        )
+    }
+}
+
+// Keyframed placement of an object. Rotation is in degrees about the object's y axis.
+pub struct ObjectAnimation {
+    pub object: Rc<dyn Hittable>,
+    pub translation: Track<Vec3>,
+    pub rotation: Track<f64>,
+    pub scale: Track<f64>,
+}
+
+impl ObjectAnimation {
+    pub fn from(object: Rc<dyn Hittable>) -> Self {
+        Self {
+            object,
+            translation: Track::constant(Vec3::new()),
+            rotation: Track::constant(0.),
+            scale: Track::constant(1.),
+        }
+    }
+
+    pub fn transform_at(&self, time: f64) -> Transform {
+        Transform::from(
+            self.object.clone(),
+            self.translation.sample(time),
+            self.rotation.sample(time),
+            self.scale.sample(time),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::Lambertian,
+        objects::{hittable::HitRecord, sphere::Sphere},
+        ray::Ray,
+        vec3::Colour,
+    };
+
+    fn track(interpolation: Interpolation) -> Track<f64> {
+        Track::new(interpolation)
+            .with_key(1., 2.)
+            .with_key(0., 0.)
+            .with_key(3., -1.)
+            .with_key(4., 5.)
+    }
+
+    #[test]
+    fn holds_end_values() {
+        for interpolation in [Interpolation::Linear, Interpolation::CatmullRom] {
+            let track = track(interpolation);
+            assert_eq!(track.sample(-5.), 0.);
+            assert_eq!(track.sample(10.), 5.);
+            assert_eq!(track.sample(f64::NEG_INFINITY), 0.);
+            assert_eq!(track.sample(f64::INFINITY), 5.);
+            assert_eq!(track.sample(f64::NAN), 0.);
+        }
+    }
+
+    #[test]
+    fn hits_keyframes_exactly() {
+        for interpolation in [Interpolation::Linear, Interpolation::CatmullRom] {
+            let track = track(interpolation);
+            for key in &track.keys {
+                assert_eq!(track.sample(key.time), key.value);
+            }
+        }
+    }
+
+    #[test]
+    fn interpolates_linearly_between_keys() {
+        let track = track(Interpolation::Linear);
+        assert_eq!(track.sample(0.5), 1.);
+        assert_eq!(track.sample(2.), 0.5);
+        assert_eq!(track.sample(3.5), 2.);
+    }
+
+    #[test]
+    fn catmull_rom_has_continuous_tangents() {
+        let track = track(Interpolation::CatmullRom);
+        let h = 1e-6;
+        for key in &track.keys[1..track.keys.len() - 1] {
+            let before = (key.value - track.sample(key.time - h)) / h;
+            let after = (track.sample(key.time + h) - key.value) / h;
+            assert!((before - after).abs() < 1e-4, "{} != {}", before, after);
+        }
+    }
+
+    #[test]
+    fn places_objects_at_their_sampled_transform() {
+        let sphere = Rc::new(Sphere::from(
+            Point3::new(),
+            1.,
+            Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5))),
+        ));
+        let mut animation = ObjectAnimation::from(sphere);
+        animation.translation = Track::new(Interpolation::Linear)
+            .with_key(0., Vec3::new())
+            .with_key(1., Vec3::from(10., 0., 0.));
+        animation.scale = Track::new(Interpolation::Linear)
+            .with_key(0., 1.)
+            .with_key(1., 2.);
+
+        let transform = animation.transform_at(0.5);
+        let r = Ray::from(Point3::from(5., 10., 0.), Vec3::from(0., -1., 0.));
+        let mut rec = HitRecord::new();
+        assert!(transform.hit(r, 0.001, f64::INFINITY, &mut rec));
+        assert!((rec.p - Point3::from(5., 1.5, 0.)).length() < 1e-9);
+        assert!((rec.normal - Vec3::from(0., 1., 0.)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    hdr::HdrImage,
+    objects::hittable::HitRecord,
+    vec3::{Colour, Point3},
+};
+use std::{collections::HashMap, rc::Rc};
+
+// Auxiliary buffers captured from the first hit of each camera ray. Albedo, normal, position and
+// depth are averaged over the samples in a pixel that hit something; pixels where every sample
+// escaped to the sky are left at zero. Object and material IDs cannot be averaged, so the first
+// hit in a pixel is kept and misses are recorded as -1.
+pub struct Aovs {
+    pub width: i32,
+    pub height: i32,
+    pub albedo: Vec<Colour>,
+    pub normal: Vec<Colour>,
+    pub position: Vec<Point3>,
+    pub depth: Vec<f64>,
+    pub hits: Vec<i32>,
+    pub object_id: Vec<Option<usize>>,
+    pub material_id: Vec<Option<usize>>,
+    material_ids: HashMap<usize, usize>,
+}
+
+impl Aovs {
+    pub fn new(width: i32, height: i32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            width,
+            height,
+            albedo: vec![Colour::new(); size],
+            normal: vec![Colour::new(); size],
+            position: vec![Point3::new(); size],
+            depth: vec![0.; size],
+            hits: vec![0; size],
+            object_id: vec![None; size],
+            material_id: vec![None; size],
+            material_ids: HashMap::new(),
+        }
+    }
+
+    pub fn add_sample(&mut self, i: i32, j: i32, hit: &HitRecord, depth: f64) {
+        let index = (j * self.width + i) as usize;
+        self.albedo[index] += hit.mat_ptr.albedo(hit);
+        self.normal[index] += hit.normal;
+        self.position[index] += hit.p;
+        self.depth[index] += depth;
+        self.hits[index] += 1;
+
+        if self.object_id[index].is_none() {
+            // Materials are shared between objects through `Rc`, so the allocation identifies
+            // the material. IDs are handed out in the order materials are first seen.
+            let key = Rc::as_ptr(&hit.mat_ptr) as *const () as usize;
+            let next_id = self.material_ids.len();
+            let material_id = *self.material_ids.entry(key).or_insert(next_id);
+
+            self.object_id[index] = Some(hit.object_id);
+            self.material_id[index] = Some(material_id);
+        }
+    }
+
+    fn image(&self) -> HdrImage {
+        HdrImage::new(self.width as usize, self.height as usize)
+    }
+
+    fn average(&self, buffer: &[Colour]) -> HdrImage {
+        let mut image = self.image();
+        for (index, value) in buffer.iter().enumerate() {
+            if self.hits[index] > 0 {
+                image.pixels[index] = *value / self.hits[index] as f64;
+            }
+        }
+        image
+    }
+
+    pub fn albedo_image(&self) -> HdrImage {
+        self.average(&self.albedo)
+    }
+
+    pub fn normal_image(&self) -> HdrImage {
+        let mut image = self.average(&self.normal);
+        for pixel in image.pixels.iter_mut() {
+            if !pixel.near_zero() {
+                *pixel = pixel.unit_vector();
+            }
+        }
+        image
+    }
+
+    pub fn position_image(&self) -> HdrImage {
+        self.average(&self.position)
+    }
+
+    pub fn depth_image(&self) -> HdrImage {
+        let depth: Vec<Colour> = self.depth.iter().map(|&d| Colour::from(d, d, d)).collect();
+        self.average(&depth)
+    }
+
+    fn id_image(&self, ids: &[Option<usize>]) -> HdrImage {
+        let mut image = self.image();
+        for (pixel, id) in image.pixels.iter_mut().zip(ids) {
+            let value = id.map_or(-1., |id| id as f64);
+            *pixel = Colour::from(value, value, value);
+        }
+        image
+    }
+
+    // Files are written to `{prefix}albedo.pfm` and so on.
+    pub fn write(&self, prefix: &str) {
+        self.albedo_image()
+            .write_pfm(&format!("{}albedo.pfm", prefix), false);
+        self.normal_image()
+            .write_pfm(&format!("{}normal.pfm", prefix), false);
+        self.position_image()
+            .write_pfm(&format!("{}position.pfm", prefix), false);
+        self.depth_image()
+            .write_pfm(&format!("{}depth.pfm", prefix), true);
+        self.id_image(&self.object_id)
+            .write_pfm(&format!("{}object_id.pfm", prefix), true);
+        self.id_image(&self.material_id)
+            .write_pfm(&format!("{}material_id.pfm", prefix), true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::{Lambertian, Metal},
+        objects::{hittable::Hittable, hittable_list::HittableList, sphere::Sphere},
+        ray::Ray,
+        vec3::Vec3,
+    };
+
+    #[test]
+    fn records_the_first_hit_of_each_pixel() {
+        let red = Colour::from(0.8, 0.1, 0.1);
+        let mut world = HittableList::new();
+        world.add(Rc::new(Sphere::from(
+            Point3::from(0., 0., -10.),
+            1.,
+            Rc::new(Metal::from(Colour::from(0.5, 0.5, 0.5), 0.)),
+        )));
+        world.add(Rc::new(Sphere::from(
+            Point3::from(0., 0., -5.),
+            1.,
+            Rc::new(Lambertian::from(red)),
+        )));
+
+        let mut aovs = Aovs::new(2, 1);
+        let r = Ray::from(Point3::new(), Vec3::from(0., 0., -1.));
+        let mut rec = HitRecord::new();
+        assert!(world.hit(r, 0.001, f64::INFINITY, &mut rec));
+        aovs.add_sample(1, 0, &rec, rec.t);
+
+        let index = 1;
+        assert!((aovs.depth_image().pixels[index] - Colour::from(4., 4., 4.)).length() < 1e-9);
+        assert!((aovs.normal_image().pixels[index] - Colour::from(0., 0., 1.)).length() < 1e-9);
+        assert!((aovs.albedo_image().pixels[index] - red).length() < 1e-9);
+        assert!((aovs.position_image().pixels[index] - Point3::from(0., 0., -4.)).length() < 1e-9);
+        assert_eq!(aovs.object_id[index], Some(1));
+        assert_eq!(aovs.material_id[index], Some(0));
+
+        // The pixel that saw nothing stays empty, with its IDs recorded as misses.
+        assert_eq!(aovs.depth_image().pixels[0].length(), 0.);
+        let object_ids = aovs.id_image(&aovs.object_id);
+        assert!((object_ids.pixels[0] - Colour::from(-1., -1., -1.)).length() == 0.);
+        assert!((object_ids.pixels[1] - Colour::from(1., 1., 1.)).length() == 0.);
+    }
+
+    #[test]
+    fn written_buffers_read_back_unchanged() {
+        let mut aovs = Aovs::new(2, 2);
+        let mut rec = HitRecord::new();
+        rec.mat_ptr = Rc::new(Lambertian::from(Colour::from(0.25, 0.5, 0.75)));
+        for (i, j, depth) in [(0, 0, 1.), (1, 0, 2.), (0, 1, 3.), (1, 1, 4.5)] {
+            rec.normal = Vec3::from(i as f64, j as f64, 1.).unit_vector();
+            aovs.add_sample(i, j, &rec, depth);
+        }
+
+        let prefix = std::env::temp_dir()
+            .join(format!("aov_round_trip_{}_", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        aovs.write(&prefix);
+
+        // Rows are stored from the bottom up as little-endian floats.
+        let path = format!("{}depth.pfm", prefix);
+        let bytes = std::fs::read(&path).unwrap();
+        let header = b"Pf\n2 2\n-1.0\n";
+        assert_eq!(&bytes[..header.len()], header);
+        assert_eq!(&bytes[header.len()..header.len() + 4], &1f32.to_le_bytes());
+
+        let depth = HdrImage::read_pfm(&path).unwrap();
+        let normal = HdrImage::read_pfm(&format!("{}normal.pfm", prefix)).unwrap();
+        for (read, written) in [(depth, aovs.depth_image()), (normal, aovs.normal_image())] {
+            assert_eq!((read.width, read.height), (2, 2));
+            for j in 0..2 {
+                for i in 0..2 {
+                    assert!((read.get(i, j) - written.get(i, j)).length() < 1e-6);
+                }
+            }
+        }
+        assert_eq!(HdrImage::read_pfm(&path).unwrap().get(1, 1).x(), 4.5);
+
+        for name in [
+            "albedo",
+            "normal",
+            "position",
+            "depth",
+            "object_id",
+            "material_id",
+        ] {
+            std::fs::remove_file(format!("{}{}.pfm", prefix, name)).unwrap();
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    hdr::HdrImage,
+    tonemap::luminance,
+    utils::{degrees_to_radians, random_f64},
+    vec3::Vec3,
+};
+use std::{f64::consts::PI, rc::Rc};
+
+// The shape of the lens opening, which sets the shape of out of focus highlights (bokeh).
+// Samples are returned in the z = 0 plane within the unit disk and are scaled by the lens radius.
+#[derive(Clone, Default)]
+pub enum Aperture {
+    #[default]
+    Circle,
+    // A regular polygon formed by `blades` straight diaphragm blades, rotated by `rotation`
+    // degrees.
+    Polygon {
+        blades: u32,
+        rotation: f64,
+    },
+    // An image of the opening stretched over the square enclosing the unit disk. Brighter pixels
+    // let through more light, and anything outside the disk is blocked by the lens barrel.
+    Mask(Rc<HdrImage>),
+}
+
+impl Aperture {
+    pub fn polygon(blades: u32, rotation: f64) -> Result<Self, String> {
+        if blades < 3 {
+            return Err(format!(
+                "An aperture needs at least 3 blades, not {}",
+                blades
+            ));
+        }
+        Ok(Self::Polygon { blades, rotation })
+    }
+
+    pub fn mask(path: &str) -> Result<Self, String> {
+        let image = HdrImage::read(path)?;
+        if !image.pixels.iter().any(|&p| luminance(p) > 0.) {
+            return Err(format!("Aperture mask {} is completely black", path));
+        }
+        Ok(Self::Mask(Rc::new(image)))
+    }
+
+    pub fn sample(&self) -> Vec3 {
+        match self {
+            Self::Circle => Vec3::random_in_unit_disk(),
+            Self::Polygon { blades, rotation } => {
+                // Pick one of the triangles between the centre and an edge, then a uniform point
+                // inside it.
+                let step = 2. * PI / *blades as f64;
+                let edge = (random_f64(0., 1.) * *blades as f64).floor();
+                let angle = degrees_to_radians(*rotation) + edge * step;
+                let a = Vec3::from(angle.cos(), angle.sin(), 0.);
+                let b = Vec3::from((angle + step).cos(), (angle + step).sin(), 0.);
+
+                let (mut r1, mut r2) = (random_f64(0., 1.), random_f64(0., 1.));
+                if r1 + r2 > 1. {
+                    r1 = 1. - r1;
+                    r2 = 1. - r2;
+                }
+                a * r1 + b * r2
+            }
+            Self::Mask(image) => loop {
+                let p = Vec3::random_in_unit_disk();
+                let transmission = luminance(image.sample((p.x() + 1.) / 2., (p.y() + 1.) / 2.));
+                if random_f64(0., 1.) < transmission {
+                    return p;
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::{dot, Colour};
+
+    #[test]
+    fn polygon_samples_stay_inside_the_blades() {
+        let (blades, rotation) = (5, 18.);
+        let aperture = Aperture::polygon(blades, rotation).unwrap();
+        let step = 2. * PI / blades as f64;
+        for _ in 0..1000 {
+            let p = aperture.sample();
+            for edge in 0..blades {
+                let angle = degrees_to_radians(rotation) + (edge as f64 + 0.5) * step;
+                let normal = Vec3::from(angle.cos(), angle.sin(), 0.);
+                assert!(dot(p, normal) <= (step / 2.).cos() + 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn mask_samples_avoid_opaque_pixels() {
+        let mut image = HdrImage::new(4, 1);
+        image.set(2, 0, Colour::from(1., 1., 1.));
+        image.set(3, 0, Colour::from(1., 1., 1.));
+        let aperture = Aperture::Mask(Rc::new(image));
+        for _ in 0..1000 {
+            let p = aperture.sample();
+            assert!(p.x() >= -0.25 && p.length() <= 1.);
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use crate::{
+    camera::aperture::Aperture,
+    objects::hittable::{HitRecord, Hittable},
+    ray::Ray,
+    utils::{degrees_to_radians, INFINITY},
+    vec3::{cross, dot, Point3, Vec3},
+};
+
+// Physical lens and sensor dimensions are given in millimetres while scenes are modelled in metres.
+pub const WORLD_UNITS_PER_MM: f64 = 0.001;
+
+pub trait CameraModel {
+    // `s` and `t` run from 0 to 1 across the image, left to right and bottom to top. Returns
+    // `None` for points the projection doesn't cover, such as outside a fisheye's image circle.
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray>;
+
+    // Camera-space depth of `p`, used for the depth AOV.
+    fn depth(&self, p: Point3) -> f64;
+
+    // Scale applied to the radiance arriving along every ray.
+    fn exposure(&self) -> f64 {
+        1.
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    // Parallel rays along the viewing direction. The viewport is `horizontal` x `vertical` in
+    // world units and lies in the plane through the camera origin.
+    Orthographic,
+}
+
+#[derive(Clone)]
+pub struct Camera {
+    projection: Projection,
+    origin: Point3,
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    lens_radius: f64,
+    focus_dist: f64,
+    aperture: Aperture,
+    cat_eye: f64,
+    exposure: f64,
+    // Normal of the plane of focus, which is `w` unless the lens is tilted.
+    focus_normal: Vec3,
+}
+
+impl Camera {
+    pub fn from(
+        look_from: Point3,
+        look_at: Point3,
+        v_up: Vec3,
+        vfov: f64,
+        aspect_ratio: f64,
+        aperture: f64,
+        focus_dist: f64,
+    ) -> Self {
+        let theta = degrees_to_radians(vfov);
+        let h = (theta / 2.).tan();
+        let viewport_height = 2. * h;
+        let viewport_width = aspect_ratio * viewport_height;
+
+        let (u, v, w) = basis(look_from, look_at, v_up);
+
+        let origin = look_from;
+        let horizontal = u * viewport_width * focus_dist;
+        let vertical = v * viewport_height * focus_dist;
+        let lower_left_corner = origin - horizontal / 2. - vertical / 2. - w * focus_dist;
+        let lens_radius = aperture / 2.;
+
+        Self {
+            projection: Projection::Perspective,
+            origin,
+            lower_left_corner,
+            horizontal,
+            vertical,
+            u,
+            v,
+            w,
+            lens_radius,
+            focus_dist,
+            aperture: Aperture::default(),
+            cat_eye: 0.,
+            exposure: 1.,
+            focus_normal: w,
+        }
+    }
+
+    pub fn focus_dist(&self) -> f64 {
+        self.focus_dist
+    }
+
+    // Depth of whatever is visible at image position (`s`, `t`) through the centre of the lens,
+    // or `None` if the ray hits nothing.
+    pub fn depth_at(&self, world: &impl Hittable, s: f64, t: f64) -> Option<f64> {
+        let pinhole = Self {
+            lens_radius: 0.,
+            cat_eye: 0.,
+            ..self.clone()
+        };
+        let ray = pinhole.get_ray(s, t)?;
+        let mut rec = HitRecord::new();
+        if world.hit(ray, 0.001, INFINITY, &mut rec) {
+            Some(self.depth(rec.p))
+        } else {
+            None
+        }
+    }
+
+    pub fn with_aperture(mut self, aperture: Aperture) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    // Optical vignetting from the lens barrel. Away from the centre of the image the opening is
+    // clipped by a second circle, shifted by `strength` lens radii at the image corners, which
+    // turns bokeh into cat's eye shapes and darkens the edges of out of focus regions.
+    pub fn with_cat_eye(mut self, strength: f64) -> Self {
+        self.cat_eye = strength;
+        self
+    }
+
+    pub fn with_exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    // Moves the lens parallel to the film by fractions of the frame's width and height, which
+    // reframes the image without converging verticals.
+    pub fn with_shift(mut self, horizontal: f64, vertical: f64) -> Self {
+        self.lower_left_corner += self.horizontal * horizontal + self.vertical * vertical;
+        self
+    }
+
+    // Tilts the plane of focus about the camera's horizontal axis by `tilt` degrees, and about
+    // its vertical axis by `swing` degrees, keeping it through the point at `focus_dist` on the
+    // optical axis.
+    pub fn with_tilt(mut self, tilt: f64, swing: f64) -> Self {
+        let (tilt, swing) = (degrees_to_radians(tilt), degrees_to_radians(swing));
+        let tilted = self.w * tilt.cos() + self.v * tilt.sin();
+        self.focus_normal = (tilted * swing.cos() + self.u * swing.sin()).unit_vector();
+        self
+    }
+
+    // Left and right eye cameras for stereoscopic rendering, separated by `eye_separation` along
+    // `u`. Rather than toeing the eyes in, both share this camera's viewport moved to
+    // `convergence_dist`, giving each eye an asymmetric frustum so that objects at that distance
+    // have zero parallax. The viewport is then scaled about each eye back to the focus distance
+    // so depth of field behaves as it does for a single camera.
+    pub fn stereo_pair(&self, eye_separation: f64, convergence_dist: f64) -> (Self, Self) {
+        assert!(
+            self.projection == Projection::Perspective,
+            "Stereo rendering needs a perspective camera"
+        );
+        let window = self.origin
+            + (self.lower_left_corner - self.origin) * (convergence_dist / self.focus_dist);
+        let scale = self.focus_dist / convergence_dist;
+
+        let eye = |side: f64| {
+            let origin = self.origin + self.u * (side * eye_separation / 2.);
+            Self {
+                origin,
+                lower_left_corner: origin + (window - origin) * scale,
+                ..self.clone()
+            }
+        };
+
+        (eye(-1.), eye(1.))
+    }
+
+    // With a non-zero aperture, rays from across the lens converge on the focal plane at
+    // `focus_dist`, giving the same depth of field as the perspective camera without changing
+    // the framing.
+    pub fn orthographic(
+        look_from: Point3,
+        look_at: Point3,
+        v_up: Vec3,
+        view_width: f64,
+        view_height: f64,
+        aperture: f64,
+        focus_dist: f64,
+    ) -> Self {
+        let (u, v, w) = basis(look_from, look_at, v_up);
+
+        let origin = look_from;
+        let horizontal = u * view_width;
+        let vertical = v * view_height;
+        let lower_left_corner = origin - horizontal / 2. - vertical / 2.;
+
+        Self {
+            projection: Projection::Orthographic,
+            origin,
+            lower_left_corner,
+            horizontal,
+            vertical,
+            u,
+            v,
+            w,
+            lens_radius: aperture / 2.,
+            focus_dist,
+            aperture: Aperture::default(),
+            cat_eye: 0.,
+            exposure: 1.,
+            focus_normal: w,
+        }
+    }
+}
+
+impl CameraModel for Camera {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let point = self.lower_left_corner + self.horizontal * s + self.vertical * t;
+        let (lens_centre, direction) = match self.projection {
+            Projection::Perspective => (self.origin, point - self.origin),
+            Projection::Orthographic => (point, -self.w),
+        };
+
+        let lens_sample = self.aperture.sample();
+        if self.cat_eye > 0. && self.lens_radius > 0. {
+            // Measured from the optical axis, so shifted lenses vignette towards the edge of the
+            // lens's image circle rather than the edge of the frame.
+            let axis = match self.projection {
+                Projection::Perspective => self.origin - self.w * self.focus_dist,
+                Projection::Orthographic => self.origin,
+            };
+            let (width, height) = (self.horizontal.length(), self.vertical.length());
+            let half_diagonal = 0.5 * (width * width + height * height).sqrt();
+            let film_offset = Vec3::from(dot(point - axis, self.u), dot(point - axis, self.v), 0.)
+                / half_diagonal;
+            if (lens_sample - film_offset * self.cat_eye).length() > 1. {
+                return None;
+            }
+        }
+
+        let rd = lens_sample * self.lens_radius;
+        let offset = self.u * rd.x() + self.v * rd.y();
+
+        // Rays from across the lens converge where the ray through its centre meets the plane of
+        // focus. If a tilted plane is never reached, that part of the image is focused at
+        // infinity instead.
+        let plane_point = self.origin - self.w * self.focus_dist;
+        let distance =
+            dot(plane_point - lens_centre, self.focus_normal) / dot(direction, self.focus_normal);
+        let ray = if distance > 0. && distance.is_finite() {
+            let focus = lens_centre + direction * distance;
+            Ray::from(lens_centre + offset, focus - (lens_centre + offset))
+        } else {
+            Ray::from(lens_centre + offset, direction)
+        };
+
+        Some(ray)
+    }
+
+    // Distance from the camera to `p` measured along the viewing direction.
+    fn depth(&self, p: Point3) -> f64 {
+        dot(p - self.origin, -self.w)
+    }
+
+    fn exposure(&self) -> f64 {
+        self.exposure
+    }
+}
+
+// Orthonormal camera basis: `w` points backwards from the view direction, `u` to the right and
+// `v` up.
+pub fn basis(look_from: Point3, look_at: Point3, v_up: Vec3) -> (Vec3, Vec3, Vec3) {
+    let w = (look_from - look_at).unit_vector();
+    let u = (cross(v_up, w)).unit_vector();
+    let v = cross(w, u);
+    (u, v, w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn near(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-9
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let look_from = Point3::from(0., 0., 5.);
+        let camera = Camera::orthographic(
+            look_from,
+            Point3::new(),
+            Vec3::from(0., 1., 0.),
+            4.,
+            2.,
+            0.,
+            5.,
+        );
+
+        for (s, t) in [(0., 0.), (1., 0.), (0.5, 0.5), (0.25, 1.)] {
+            let ray = camera.get_ray(s, t).unwrap();
+            assert!(near(ray.direction().unit_vector(), Vec3::from(0., 0., -1.)));
+            assert!(near(
+                ray.origin(),
+                Point3::from(4. * (s - 0.5), 2. * (t - 0.5), 5.)
+            ));
+        }
+    }
+
+    // Where a ray crosses the plane z = `z`.
+    fn at_depth(ray: Ray, z: f64) -> Point3 {
+        ray.origin() + ray.direction() * ((z - ray.origin().z()) / ray.direction().z())
+    }
+
+    #[test]
+    fn stereo_eyes_converge_without_toeing_in() {
+        let camera = Camera::from(
+            Point3::new(),
+            Point3::from(0., 0., -1.),
+            Vec3::from(0., 1., 0.),
+            90.,
+            1.5,
+            0.,
+            10.,
+        );
+        let (left, right) = camera.stereo_pair(0.065, 5.);
+
+        let (left_eye, right_eye) = (
+            left.get_ray(0.5, 0.5).unwrap().origin(),
+            right.get_ray(0.5, 0.5).unwrap().origin(),
+        );
+        assert!(near(left_eye, Point3::from(-0.0325, 0., 0.)));
+        assert!(near(right_eye, Point3::from(0.0325, 0., 0.)));
+
+        // Points at the convergence distance have zero parallax, while the eyes keep looking
+        // along the same direction.
+        for (s, t) in [(0.5, 0.5), (0.1, 0.8), (1., 0.)] {
+            let (l, r) = (left.get_ray(s, t).unwrap(), right.get_ray(s, t).unwrap());
+            assert!(near(at_depth(l, -5.), at_depth(r, -5.)));
+            assert!(!near(at_depth(l, -20.), at_depth(r, -20.)));
+        }
+        let p = Point3::from(0.3, 0.2, -7.);
+        assert!((left.depth(p) - 7.).abs() < 1e-12 && (right.depth(p) - 7.).abs() < 1e-12);
+    }
+
+    fn looking_down_z(aperture: f64) -> Camera {
+        Camera::from(
+            Point3::new(),
+            Point3::from(0., 0., -1.),
+            Vec3::from(0., 1., 0.),
+            90.,
+            1.,
+            aperture,
+            10.,
+        )
+    }
+
+    #[test]
+    fn shift_moves_the_frustum_without_rotating_it() {
+        let camera = looking_down_z(0.);
+        let shifted = looking_down_z(0.).with_shift(0.1, 0.25);
+
+        for (s, t) in [(0.5, 0.5), (0., 0.), (0.9, 0.75)] {
+            let ray = shifted.get_ray(s, t).unwrap();
+            let expected = camera.get_ray(s + 0.1, t + 0.25).unwrap();
+            assert!(near(ray.origin(), expected.origin()));
+            assert!(near(ray.direction(), expected.direction()));
+        }
+        // The film stays parallel to the lens, so depth is still measured along the same axis.
+        let p = Point3::from(3., -2., -6.);
+        assert_eq!(shifted.depth(p), camera.depth(p));
+    }
+
+    #[test]
+    fn tilt_rotates_the_plane_of_focus() {
+        let tilt = 30f64;
+        let normal = Vec3::from(0., tilt.to_radians().sin(), tilt.to_radians().cos());
+        let camera = looking_down_z(1.).with_tilt(tilt, 0.);
+
+        for (s, t) in [(0.5, 0.5), (0.5, 0.9), (0.2, 0.1)] {
+            // Every ray through a pixel meets the ray through the centre of the lens where it
+            // crosses the tilted plane through the focus point on the axis.
+            let centre = looking_down_z(0.).get_ray(s, t).unwrap().direction();
+            let distance = dot(Point3::from(0., 0., -10.), normal) / dot(centre, normal);
+            let focus = centre * distance;
+            assert!((dot(focus - Point3::from(0., 0., -10.), normal)).abs() < 1e-9);
+            if t != 0.5 {
+                assert!((camera.depth(focus) - 10.).abs() > 0.5);
+            }
+
+            for _ in 0..20 {
+                let ray = camera.get_ray(s, t).unwrap();
+                let to_focus = focus - ray.origin();
+                assert!(cross(to_focus, ray.direction()).length() < 1e-9 * to_focus.length());
+            }
+        }
+    }
+}
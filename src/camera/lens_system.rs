@@ -0,0 +1,302 @@
+use crate::{
+    camera::camera::{basis, CameraModel, WORLD_UNITS_PER_MM},
+    ray::Ray,
+    vec3::{dot, Point3, Vec3},
+};
+use std::fs;
+
+// One spherical surface of a lens prescription. All lengths are in millimetres.
+#[derive(Copy, Clone, Debug)]
+pub struct LensElement {
+    // Positive when the centre of curvature is on the film side. Zero marks the aperture stop.
+    pub curvature_radius: f64,
+    // Distance along the axis to the next surface towards the film.
+    pub thickness: f64,
+    // Refractive index of the medium between this surface and the next one towards the film,
+    // where 0 is treated as air.
+    pub ior: f64,
+    pub aperture_radius: f64,
+}
+
+impl LensElement {
+    // Reads a prescription with one surface per line, ordered from the front of the lens to the
+    // back: curvature radius, thickness, index of refraction and aperture diameter. Blank lines
+    // and lines starting with '#' are ignored.
+    pub fn load(path: &str) -> Result<Vec<Self>, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|error| format!("Unable to read lens file {}: {}", path, error))?;
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let values = line
+                    .split_whitespace()
+                    .map(|v| {
+                        v.parse()
+                            .map_err(|_| format!("Invalid lens file value: {}", v))
+                    })
+                    .collect::<Result<Vec<f64>, _>>()?;
+                if values.len() != 4 {
+                    return Err(format!("Lens file lines need 4 values: {}", line));
+                }
+                Ok(Self {
+                    curvature_radius: values[0],
+                    thickness: values[1],
+                    ior: values[2],
+                    aperture_radius: values[3] / 2.,
+                })
+            })
+            .collect()
+    }
+
+    fn medium_ior(&self) -> f64 {
+        if self.ior == 0. {
+            1.
+        } else {
+            self.ior
+        }
+    }
+}
+
+// A camera that traces rays from the film through a stack of spherical lens elements. In lens
+// space the film sits at z = 0 and the lens extends towards +z, with the scene beyond it. Rays
+// blocked by an element's housing produce no ray, so vignetting and aberrations come from the
+// lens itself. The result is normalised so the centre of the image is as bright as with the
+// other cameras.
+pub struct LensSystem {
+    elements: Vec<LensElement>,
+    // Axial position of each element's vertex.
+    z: Vec<f64>,
+    film_width: f64,
+    film_height: f64,
+    origin: Point3,
+    right: Vec3,
+    up: Vec3,
+    forward: Vec3,
+    exposure: f64,
+}
+
+impl LensSystem {
+    // `film_width` is in millimetres and `focus_dist` in world units from the film. The lens is
+    // moved along its axis so that the focus distance is sharp, using its thick lens
+    // approximation.
+    pub fn from(
+        look_from: Point3,
+        look_at: Point3,
+        v_up: Vec3,
+        elements: Vec<LensElement>,
+        film_width: f64,
+        aspect_ratio: f64,
+        focus_dist: f64,
+    ) -> Result<Self, String> {
+        if elements.is_empty() {
+            return Err("A lens system needs at least one element".to_string());
+        }
+        let (u, v, w) = basis(look_from, look_at, v_up);
+
+        let mut z = vec![0.; elements.len()];
+        let mut position = 0.;
+        for (i, element) in elements.iter().enumerate().rev() {
+            position += element.thickness;
+            z[i] = position;
+        }
+
+        let mut lens = Self {
+            elements,
+            z,
+            film_width,
+            film_height: film_width / aspect_ratio,
+            origin: look_from,
+            right: u,
+            up: v,
+            forward: -w,
+            exposure: 1.,
+        };
+        lens.focus(focus_dist / WORLD_UNITS_PER_MM)?;
+        lens.exposure = 1. / lens.axial_transmission()?;
+        Ok(lens)
+    }
+
+    fn rear(&self) -> (f64, f64) {
+        let last = self.elements.len() - 1;
+        (self.z[last], self.elements[last].aperture_radius)
+    }
+
+    // Traces a ray through every element, towards the scene or towards the film. Returns `None`
+    // if it misses an element or is totally internally reflected.
+    fn trace(&self, mut o: Point3, mut d: Vec3, towards_scene: bool) -> Option<(Point3, Vec3)> {
+        let n = self.elements.len();
+        let order: Vec<usize> = if towards_scene {
+            (0..n).rev().collect()
+        } else {
+            (0..n).collect()
+        };
+
+        for i in order {
+            let element = self.elements[i];
+            let radius = element.curvature_radius;
+
+            let (p, normal) = if radius == 0. {
+                let t = (self.z[i] - o.z()) / d.z();
+                if t < 0. {
+                    return None;
+                }
+                (o + d * t, None)
+            } else {
+                let centre = Point3::from(0., 0., self.z[i] - radius);
+                let oc = o - centre;
+                let half_b = dot(oc, d);
+                let c = oc.squared() - radius * radius;
+                let discriminant = half_b * half_b - d.squared() * c;
+                if discriminant < 0. {
+                    return None;
+                }
+
+                // Pick the intersection on the cap of the sphere that forms the surface.
+                let use_closer = (d.z() > 0.) == (radius < 0.);
+                let root = if use_closer {
+                    (-half_b - discriminant.sqrt()) / d.squared()
+                } else {
+                    (-half_b + discriminant.sqrt()) / d.squared()
+                };
+                if root < 0. {
+                    return None;
+                }
+                let p = o + d * root;
+                (p, Some((p - centre) / radius.abs()))
+            };
+
+            if p.x() * p.x() + p.y() * p.y() > element.aperture_radius * element.aperture_radius {
+                return None;
+            }
+            o = p;
+
+            if let Some(mut normal) = normal {
+                if dot(normal, d) > 0. {
+                    normal = -normal;
+                }
+                let film_side = element.medium_ior();
+                let scene_side = if i == 0 {
+                    1.
+                } else {
+                    self.elements[i - 1].medium_ior()
+                };
+                let (eta_i, eta_t) = if towards_scene {
+                    (film_side, scene_side)
+                } else {
+                    (scene_side, film_side)
+                };
+                d = refract(d.unit_vector(), normal, eta_i / eta_t)?;
+            }
+        }
+
+        Some((o, d))
+    }
+
+    // Returns the axial positions of the focal point and principal plane found by tracing a ray
+    // parallel to the axis into the lens.
+    fn cardinal_points(&self, towards_scene: bool) -> Result<(f64, f64), String> {
+        let (start, height) = if towards_scene {
+            let (rear_z, rear_radius) = self.rear();
+            (rear_z - 1., 0.01 * rear_radius)
+        } else {
+            (self.z[0] + 1., 0.01 * self.elements[0].aperture_radius)
+        };
+        let direction = if towards_scene { 1. } else { -1. };
+
+        let (o, d) = self
+            .trace(
+                Point3::from(height, 0., start),
+                Vec3::from(0., 0., direction),
+                towards_scene,
+            )
+            .ok_or_else(|| "Paraxial ray could not pass through the lens system".to_string())?;
+
+        let focal_point = o.z() + d.z() * (-o.x() / d.x());
+        let principal_plane = o.z() + d.z() * ((height - o.x()) / d.x());
+        Ok((focal_point, principal_plane))
+    }
+
+    // Moves the lens so that a point `focus_dist` millimetres in front of the film is in focus.
+    fn focus(&mut self, focus_dist: f64) -> Result<(), String> {
+        let (film_focal_point, film_principal_plane) = self.cardinal_points(false)?;
+        let (_, scene_principal_plane) = self.cardinal_points(true)?;
+        let f = film_principal_plane - film_focal_point;
+
+        // With the lens shifted by `delta`, the thin lens equation 1 / d_o + 1 / d_i = 1 / f
+        // becomes a quadratic in `delta`.
+        let a = focus_dist - scene_principal_plane;
+        let b = film_principal_plane;
+        let discriminant = (a + b) * (a + b) - 4. * (a + b) * f;
+        if discriminant < 0. {
+            return Err("Focus distance is too close for the lens system".to_string());
+        }
+        let delta = ((a - b) - discriminant.sqrt()) / 2.;
+
+        for z in self.z.iter_mut() {
+            *z += delta;
+        }
+        Ok(())
+    }
+
+    fn sample_rear(&self) -> Point3 {
+        let (rear_z, rear_radius) = self.rear();
+        let p = Vec3::random_in_unit_disk() * rear_radius;
+        Point3::from(p.x(), p.y(), rear_z)
+    }
+
+    // Fraction of rays from the centre of the film towards the rear element that make it out of
+    // the lens.
+    fn axial_transmission(&self) -> Result<f64, String> {
+        let samples = 4096;
+        let passed = (0..samples)
+            .filter(|_| {
+                let d = self.sample_rear().unit_vector();
+                self.trace(Point3::new(), d, true).is_some()
+            })
+            .count();
+        if passed == 0 {
+            return Err("No light passes through the lens system".to_string());
+        }
+        Ok(passed as f64 / samples as f64)
+    }
+}
+
+impl CameraModel for LensSystem {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        // The lens inverts the image, so the film is read back to front.
+        let film = Point3::from(
+            -(s - 0.5) * self.film_width,
+            -(t - 0.5) * self.film_height,
+            0.,
+        );
+        let d = (self.sample_rear() - film).unit_vector();
+        let (o, d) = self.trace(film, d, true)?;
+
+        let to_world = |v: Vec3| self.right * v.x() + self.up * v.y() + self.forward * v.z();
+        Some(Ray::from(
+            self.origin + to_world(o) * WORLD_UNITS_PER_MM,
+            to_world(d),
+        ))
+    }
+
+    fn depth(&self, p: Point3) -> f64 {
+        dot(p - self.origin, self.forward)
+    }
+
+    fn exposure(&self) -> f64 {
+        self.exposure
+    }
+}
+
+// Refracts unit vector `uv` through a surface with normal `n` facing against it, or returns
+// `None` on total internal reflection.
+fn refract(uv: Vec3, n: Vec3, eta: f64) -> Option<Vec3> {
+    let cos_i = -dot(uv, n);
+    let sin2_t = eta * eta * (1. - cos_i * cos_i);
+    if sin2_t > 1. {
+        return None;
+    }
+    Some(uv * eta + n * (eta * cos_i - (1. - sin2_t).sqrt()))
+}
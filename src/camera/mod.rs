@@ -0,0 +1,6 @@
+pub mod aperture;
+#[allow(clippy::module_inception)]
+pub mod camera;
+pub mod lens_system;
+pub mod panoramic;
+pub mod physical;
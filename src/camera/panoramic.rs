@@ -0,0 +1,202 @@
+use crate::{
+    camera::camera::{basis, CameraModel},
+    ray::Ray,
+    utils::degrees_to_radians,
+    vec3::{Point3, Vec3},
+};
+use std::f64::consts::PI;
+
+// Panoramic cameras are pinholes: every ray starts at `origin` and depth is the distance from
+// it. The view is oriented so the centre of the image looks towards `look_at`.
+struct Frame {
+    origin: Point3,
+    right: Vec3,
+    up: Vec3,
+    forward: Vec3,
+}
+
+impl Frame {
+    fn from(look_from: Point3, look_at: Point3, v_up: Vec3) -> Self {
+        let (u, v, w) = basis(look_from, look_at, v_up);
+        Self {
+            origin: look_from,
+            right: u,
+            up: v,
+            forward: -w,
+        }
+    }
+
+    fn ray(&self, direction: Vec3) -> Ray {
+        Ray::from(self.origin, direction)
+    }
+
+    fn depth(&self, p: Point3) -> f64 {
+        (p - self.origin).length()
+    }
+}
+
+// Maps longitude across the full width of the image and latitude across its height, covering
+// 360 x 180 degrees. The image should have a 2:1 aspect ratio for square pixels.
+pub struct Equirectangular {
+    frame: Frame,
+}
+
+impl Equirectangular {
+    pub fn from(look_from: Point3, look_at: Point3, v_up: Vec3) -> Self {
+        Self {
+            frame: Frame::from(look_from, look_at, v_up),
+        }
+    }
+}
+
+impl CameraModel for Equirectangular {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let phi = (s - 0.5) * 2. * PI;
+        let theta = (t - 0.5) * PI;
+        let f = &self.frame;
+        let direction =
+            (f.right * phi.sin() + f.forward * phi.cos()) * theta.cos() + f.up * theta.sin();
+
+        Some(f.ray(direction))
+    }
+
+    fn depth(&self, p: Point3) -> f64 {
+        self.frame.depth(p)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FisheyeMapping {
+    // Image radius is proportional to the angle from the optical axis.
+    Equidistant,
+    // Image radius is proportional to sin(angle / 2), preserving solid angle.
+    Equisolid,
+}
+
+// The image circle is inscribed in the image height, with `fov` degrees across its diameter.
+// Points outside the circle produce no ray.
+pub struct Fisheye {
+    frame: Frame,
+    mapping: FisheyeMapping,
+    fov: f64,
+    aspect_ratio: f64,
+}
+
+impl Fisheye {
+    pub fn from(
+        look_from: Point3,
+        look_at: Point3,
+        v_up: Vec3,
+        mapping: FisheyeMapping,
+        fov: f64,
+        aspect_ratio: f64,
+    ) -> Self {
+        Self {
+            frame: Frame::from(look_from, look_at, v_up),
+            mapping,
+            fov: degrees_to_radians(fov),
+            aspect_ratio,
+        }
+    }
+}
+
+impl CameraModel for Fisheye {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let x = (s - 0.5) * self.aspect_ratio * 2.;
+        let y = (t - 0.5) * 2.;
+        let r = (x * x + y * y).sqrt();
+        if r > 1. {
+            return None;
+        }
+
+        let half_fov = self.fov / 2.;
+        let theta = match self.mapping {
+            FisheyeMapping::Equidistant => r * half_fov,
+            FisheyeMapping::Equisolid => 2. * (r * (half_fov / 2.).sin()).asin(),
+        };
+        let (cos_phi, sin_phi) = if r > 0. { (x / r, y / r) } else { (1., 0.) };
+
+        let f = &self.frame;
+        let direction =
+            f.forward * theta.cos() + (f.right * cos_phi + f.up * sin_phi) * theta.sin();
+        Some(f.ray(direction))
+    }
+
+    fn depth(&self, p: Point3) -> f64 {
+        self.frame.depth(p)
+    }
+}
+
+// Renders all six 90 degree faces into a 3 x 2 grid of square tiles, so the image should have a
+// 3:2 aspect ratio. The top row holds the left, front and right faces and the bottom row the
+// back, up and down faces.
+pub struct CubeMap {
+    frame: Frame,
+}
+
+impl CubeMap {
+    pub fn from(look_from: Point3, look_at: Point3, v_up: Vec3) -> Self {
+        Self {
+            frame: Frame::from(look_from, look_at, v_up),
+        }
+    }
+
+    // Returns the (forward, right, up) axes of a face.
+    fn face(&self, column: usize, row: usize) -> (Vec3, Vec3, Vec3) {
+        let f = &self.frame;
+        match (row, column) {
+            (1, 0) => (-f.right, f.forward, f.up),
+            (1, 1) => (f.forward, f.right, f.up),
+            (1, 2) => (f.right, -f.forward, f.up),
+            (0, 0) => (-f.forward, -f.right, f.up),
+            (0, 1) => (f.up, f.right, -f.forward),
+            _ => (-f.up, f.right, f.forward),
+        }
+    }
+}
+
+impl CameraModel for CubeMap {
+    fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let x = (s * 3.).clamp(0., 2.999_999);
+        let y = (t * 2.).clamp(0., 1.999_999);
+        let (column, row) = (x as usize, y as usize);
+        let a = (x - column as f64) * 2. - 1.;
+        let b = (y - row as f64) * 2. - 1.;
+
+        let (forward, right, up) = self.face(column, row);
+        Some(self.frame.ray(forward + right * a + up * b))
+    }
+
+    fn depth(&self, p: Point3) -> f64 {
+        self.frame.depth(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equirectangular_maps_the_image_onto_the_sphere() {
+        let camera = Equirectangular::from(
+            Point3::from(1., 2., 3.),
+            Point3::from(1., 2., 0.),
+            Vec3::from(0., 1., 0.),
+        );
+        let expected = [
+            ((0.5, 0.5), Vec3::from(0., 0., -1.)),
+            ((0.75, 0.5), Vec3::from(1., 0., 0.)),
+            ((0.25, 0.5), Vec3::from(-1., 0., 0.)),
+            ((0., 0.5), Vec3::from(0., 0., 1.)),
+            ((1., 0.5), Vec3::from(0., 0., 1.)),
+            ((0.5, 1.), Vec3::from(0., 1., 0.)),
+            ((0.5, 0.), Vec3::from(0., -1., 0.)),
+        ];
+
+        for ((s, t), direction) in expected {
+            let ray = camera.get_ray(s, t).unwrap();
+            assert!((ray.origin() - Point3::from(1., 2., 3.)).length() < 1e-12);
+            assert!((ray.direction().unit_vector() - direction).length() < 1e-9);
+        }
+    }
+}
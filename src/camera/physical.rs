@@ -0,0 +1,108 @@
+use crate::{
+    camera::camera::{Camera, WORLD_UNITS_PER_MM},
+    objects::hittable::Hittable,
+    vec3::{Point3, Vec3},
+};
+
+// Describes a thin lens camera the way a photographer would set one up. Sensor dimensions and
+// focal length are in millimetres, `focus_dist` is in world units and `shutter_speed` is in
+// seconds. `shift` and `tilt` set up a tilt-shift lens as `Camera::with_shift` and
+// `Camera::with_tilt` do, with the tilt and swing in degrees.
+#[derive(Copy, Clone, Debug)]
+pub struct PhysicalCamera {
+    pub look_from: Point3,
+    pub look_at: Point3,
+    pub v_up: Vec3,
+    pub sensor_width: f64,
+    pub sensor_height: f64,
+    pub focal_length: f64,
+    pub f_stop: f64,
+    pub focus_dist: f64,
+    pub shutter_speed: f64,
+    pub iso: f64,
+    pub shift: (f64, f64),
+    pub tilt: (f64, f64),
+}
+
+impl PhysicalCamera {
+    pub fn vfov(&self) -> f64 {
+        (2. * (self.sensor_height / (2. * self.focal_length)).atan()).to_degrees()
+    }
+
+    pub fn aperture(&self) -> f64 {
+        self.focal_length / self.f_stop * WORLD_UNITS_PER_MM
+    }
+
+    // Relative exposure from the shutter speed, ISO and f-number. The scene's sky has unit
+    // radiance, so this is calibrated with the "sunny 16" rule: f/16 at 1/100 s and ISO 100 gives
+    // an exposure of 1.
+    pub fn exposure(&self) -> f64 {
+        self.shutter_speed * self.iso * (16. / self.f_stop).powi(2)
+    }
+
+    pub fn build(&self) -> Camera {
+        Camera::from(
+            self.look_from,
+            self.look_at,
+            self.v_up,
+            self.vfov(),
+            self.sensor_width / self.sensor_height,
+            self.aperture(),
+            self.focus_dist,
+        )
+        .with_exposure(self.exposure())
+        .with_shift(self.shift.0, self.shift.1)
+        .with_tilt(self.tilt.0, self.tilt.1)
+    }
+
+    // Focuses on whatever is visible at image position (`s`, `t`) by casting a ray through the
+    // centre of the shifted lens. The focus distance is left unchanged if the ray hits nothing.
+    pub fn auto_focus(&mut self, world: &impl Hittable, s: f64, t: f64) {
+        if let Some(depth) = self.build().depth_at(world, s, t) {
+            self.focus_dist = depth;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::Lambertian,
+        objects::{hittable_list::HittableList, sphere::Sphere},
+        vec3::Colour,
+    };
+    use std::rc::Rc;
+
+    #[test]
+    fn auto_focuses_through_the_shifted_lens() {
+        // A sphere above the optical axis that only the shifted view has at its centre.
+        let mut world = HittableList::new();
+        world.add(Rc::new(Sphere::from(
+            Point3::from(0., 2., -4.),
+            0.5,
+            Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5))),
+        )));
+
+        let mut camera = PhysicalCamera {
+            look_from: Point3::new(),
+            look_at: Point3::from(0., 0., -1.),
+            v_up: Vec3::from(0., 1., 0.),
+            sensor_width: 36.,
+            sensor_height: 36.,
+            focal_length: 36.,
+            f_stop: 2.,
+            focus_dist: 10.,
+            shutter_speed: 0.01,
+            iso: 100.,
+            shift: (0., 0.),
+            tilt: (0., 0.),
+        };
+        camera.auto_focus(&world, 0.5, 0.5);
+        assert_eq!(camera.focus_dist, 10.);
+
+        camera.shift = (0., 0.5);
+        camera.auto_focus(&world, 0.5, 0.5);
+        assert!((camera.focus_dist - 3.5).abs() < 0.1);
+    }
+}
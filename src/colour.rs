@@ -1,10 +1,15 @@
-use crate::{utils::clamp, vec3::Colour};
+use crate::{tonemap::ToneMapper, utils::clamp, vec3::Colour};
 
-pub fn rescale_colour(pixel_colour: Colour, samples_per_pixel: i32) -> (i32, i32, i32) {
+pub fn rescale_colour(
+    pixel_colour: Colour,
+    samples_per_pixel: i32,
+    tone_mapper: &ToneMapper,
+) -> (i32, i32, i32) {
     let scale = 1.0 / samples_per_pixel as f64;
-    let r = (scale * pixel_colour.x()).sqrt();
-    let g = (scale * pixel_colour.y()).sqrt();
-    let b = (scale * pixel_colour.z()).sqrt();
+    let mapped = tone_mapper.apply(pixel_colour * scale);
+    let r = mapped.x().max(0.).sqrt();
+    let g = mapped.y().max(0.).sqrt();
+    let b = mapped.z().max(0.).sqrt();
 
     (
         (256. * clamp(r, 0.0, 0.999)) as i32,
@@ -0,0 +1,203 @@
+use crate::{hdr::HdrImage, vec3::Colour};
+
+// Joint bilateral denoiser. Each pixel becomes a weighted average of its neighbours, where the
+// weights fall off with screen distance and with differences in colour and, when available, in
+// the first-hit albedo and normal guide buffers. The guides are noise free compared to the
+// beauty pass, so they keep edges and texture sharp while flat regions are smoothed.
+pub struct Denoiser {
+    pub radius: usize,
+    pub sigma_spatial: f64,
+    pub sigma_colour: f64,
+    pub sigma_albedo: f64,
+    pub sigma_normal: f64,
+}
+
+impl Denoiser {
+    pub fn new() -> Self {
+        Self {
+            radius: 5,
+            sigma_spatial: 3.,
+            sigma_colour: 0.6,
+            sigma_albedo: 0.1,
+            sigma_normal: 0.3,
+        }
+    }
+
+    pub fn denoise(
+        &self,
+        beauty: &HdrImage,
+        albedo: Option<&HdrImage>,
+        normal: Option<&HdrImage>,
+    ) -> Result<HdrImage, String> {
+        for guide in albedo.iter().chain(normal.iter()) {
+            if guide.width != beauty.width || guide.height != beauty.height {
+                return Err(format!(
+                    "Guide buffers must be {}x{}, the size of the image being denoised, not {}x{}",
+                    beauty.width, beauty.height, guide.width, guide.height
+                ));
+            }
+        }
+
+        // Filtering irradiance rather than radiance stops texture detail from being blurred; the
+        // albedo is multiplied back in afterwards.
+        let irradiance = match albedo {
+            Some(albedo) => modulate(beauty, albedo, |c, a| c / a),
+            None => beauty.clone(),
+        };
+
+        let mut output = HdrImage::new(beauty.width, beauty.height);
+        let radius = self.radius as i64;
+
+        for j in 0..beauty.height {
+            for i in 0..beauty.width {
+                let centre = irradiance.get(i, j);
+                let mut sum = Colour::new();
+                let mut total_weight = 0.;
+
+                for dj in -radius..=radius {
+                    for di in -radius..=radius {
+                        let (qi, qj) = (i as i64 + di, j as i64 + dj);
+                        if qi < 0
+                            || qj < 0
+                            || qi >= beauty.width as i64
+                            || qj >= beauty.height as i64
+                        {
+                            continue;
+                        }
+                        let (qi, qj) = (qi as usize, qj as usize);
+                        let neighbour = irradiance.get(qi, qj);
+
+                        let mut exponent = (di * di + dj * dj) as f64 / self.sigma_spatial.powi(2)
+                            + (centre - neighbour).squared() / self.sigma_colour.powi(2);
+                        if let Some(albedo) = albedo {
+                            exponent += (albedo.get(i, j) - albedo.get(qi, qj)).squared()
+                                / self.sigma_albedo.powi(2);
+                        }
+                        if let Some(normal) = normal {
+                            exponent += (normal.get(i, j) - normal.get(qi, qj)).squared()
+                                / self.sigma_normal.powi(2);
+                        }
+
+                        let weight = (-0.5 * exponent).exp();
+                        sum += neighbour * weight;
+                        total_weight += weight;
+                    }
+                }
+
+                output.set(i, j, sum / total_weight);
+            }
+        }
+
+        Ok(match albedo {
+            Some(albedo) => modulate(&output, albedo, |c, a| c * a),
+            None => output,
+        })
+    }
+}
+
+// Pixels without a meaningful albedo (e.g. the sky) are left as they are.
+fn modulate(image: &HdrImage, albedo: &HdrImage, f: impl Fn(f64, f64) -> f64) -> HdrImage {
+    let mut output = image.clone();
+    for (pixel, a) in output.pixels.iter_mut().zip(&albedo.pixels) {
+        for c in 0..3 {
+            if a[c] > 1e-3 {
+                pixel.e[c] = f(pixel.e[c], a[c]);
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_guides_of_the_wrong_size() {
+        let beauty = HdrImage::new(4, 3);
+        let guide = HdrImage::new(3, 4);
+        let denoiser = Denoiser::new();
+        assert!(denoiser.denoise(&beauty, Some(&guide), None).is_err());
+        assert!(denoiser.denoise(&beauty, None, Some(&guide)).is_err());
+        assert!(denoiser
+            .denoise(&beauty, Some(&beauty), Some(&beauty))
+            .is_ok());
+    }
+
+    const SIZE: usize = 16;
+
+    fn image(f: impl Fn(usize, usize) -> Colour) -> HdrImage {
+        let mut image = HdrImage::new(SIZE, SIZE);
+        for j in 0..SIZE {
+            for i in 0..SIZE {
+                image.set(i, j, f(i, j));
+            }
+        }
+        image
+    }
+
+    fn grey(value: f64) -> Colour {
+        Colour::from(value, value, value)
+    }
+
+    // Deterministic noise in [-0.1, 0.1].
+    fn noise(i: usize, j: usize) -> f64 {
+        ((i * 7 + j * 13) % 11) as f64 / 50. - 0.1
+    }
+
+    fn variance(image: &HdrImage, mean: f64) -> f64 {
+        image
+            .pixels
+            .iter()
+            .map(|p| (p.x() - mean).powi(2))
+            .sum::<f64>()
+            / image.pixels.len() as f64
+    }
+
+    #[test]
+    fn smooths_flat_regions() {
+        let beauty = image(|i, j| grey(0.5 + noise(i, j)));
+        let albedo = image(|_, _| grey(0.5));
+        let normal = image(|_, _| Colour::from(0., 0., 1.));
+
+        let denoised = Denoiser::new()
+            .denoise(&beauty, Some(&albedo), Some(&normal))
+            .unwrap();
+        assert!(variance(&denoised, 0.5) < 0.1 * variance(&beauty, 0.5));
+    }
+
+    #[test]
+    fn keeps_edges_in_the_guides() {
+        let left = |i: usize| i < SIZE / 2;
+        let normal = image(|_, _| Colour::from(0., 0., 1.));
+        let albedo_edge = image(|i, _| grey(if left(i) { 0.2 } else { 0.8 }));
+        let flat_albedo = image(|_, _| grey(0.5));
+        let normal_edge = image(|i, _| {
+            if left(i) {
+                Colour::from(0., 0., 1.)
+            } else {
+                Colour::from(1., 0., 0.)
+            }
+        });
+
+        // The step in the beauty pass is too small for the colour weight alone to keep it.
+        let step = image(|i, _| grey(if left(i) { 0.45 } else { 0.55 }));
+        let unguided = Denoiser::new().denoise(&step, None, None).unwrap();
+        assert!((unguided.get(SIZE / 2, 0) - step.get(SIZE / 2, 0)).length() > 0.01);
+
+        let albedo_step = image(|i, _| grey(if left(i) { 0.2 } else { 0.8 }));
+        for (beauty, albedo, normal) in [
+            (&albedo_step, &albedo_edge, &normal),
+            (&step, &flat_albedo, &normal_edge),
+        ] {
+            let denoised = Denoiser::new()
+                .denoise(beauty, Some(albedo), Some(normal))
+                .unwrap();
+            for j in 0..SIZE {
+                for i in SIZE / 2 - 1..=SIZE / 2 {
+                    assert!((denoised.get(i, j) - beauty.get(i, j)).length() < 0.01);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    filter::Filter,
+    hdr::HdrImage,
+    spectrum::spectrum_to_rgb,
+    vec3::{Colour, Vec3},
+};
+
+// Float framebuffer accumulating filter-weighted samples. Each sample is splatted onto every
+// pixel whose centre lies within the filter radius, so pixel values are normalised by the sum of
+// the weights they received rather than by a sample count.
+pub struct Film {
+    pub width: i32,
+    pub height: i32,
+    pub filter: Filter,
+    pub pixels: Vec<Colour>,
+    pub weights: Vec<f64>,
+}
+
+impl Film {
+    pub fn new(width: i32, height: i32, filter: Filter) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            width,
+            height,
+            filter,
+            pixels: vec![Colour::new(); size],
+            weights: vec![0.; size],
+        }
+    }
+
+    fn index(&self, i: i32, j: i32) -> usize {
+        (j * self.width + i) as usize
+    }
+
+    // `x` and `y` are continuous image coordinates, with pixel (i, j) covering [i, i + 1) x [j, j + 1).
+    pub fn add_sample(&mut self, x: f64, y: f64, colour: Colour) {
+        let radius = self.filter.radius;
+        let i_min = ((x - 0.5 - radius).ceil() as i32).max(0);
+        let i_max = ((x - 0.5 + radius).floor() as i32).min(self.width - 1);
+        let j_min = ((y - 0.5 - radius).ceil() as i32).max(0);
+        let j_max = ((y - 0.5 + radius).floor() as i32).min(self.height - 1);
+
+        for j in j_min..=j_max {
+            for i in i_min..=i_max {
+                let weight = self.filter.evaluate(i as f64 + 0.5 - x, j as f64 + 0.5 - y);
+                if weight != 0. {
+                    let index = self.index(i, j);
+                    self.pixels[index] += colour * weight;
+                    self.weights[index] += weight;
+                }
+            }
+        }
+    }
+
+    // Adds a spectral mode sample carrying radiance at the three `wavelengths`.
+    pub fn add_spectral_sample(&mut self, x: f64, y: f64, radiance: Vec3, wavelengths: Vec3) {
+        self.add_sample(x, y, spectrum_to_rgb(radiance, wavelengths));
+    }
+
+    pub fn colour(&self, i: i32, j: i32) -> Colour {
+        let index = self.index(i, j);
+        if self.weights[index] > 0. {
+            self.pixels[index] / self.weights[index]
+        } else {
+            Colour::new()
+        }
+    }
+
+    pub fn to_hdr(&self) -> HdrImage {
+        let mut hdr = HdrImage::new(self.width as usize, self.height as usize);
+        for j in 0..self.height {
+            for i in 0..self.width {
+                hdr.set(i as usize, j as usize, self.colour(i, j));
+            }
+        }
+
+        hdr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::FilterKind;
+
+    #[test]
+    fn normalises_pixels_by_their_filter_weights() {
+        let mut film = Film::new(20, 20, Filter::from(FilterKind::Tent, 1.));
+        film.add_sample(10.5, 10.5, Colour::from(1., 0., 0.));
+        film.add_sample(11.2, 10.5, Colour::from(0., 1., 0.));
+
+        assert!((film.colour(10, 10).x() - 1. / 1.3).abs() < 1e-12);
+        assert!((film.colour(10, 10).y() - 0.3 / 1.3).abs() < 1e-12);
+        assert_eq!(film.colour(11, 10).x(), 0.);
+        assert!((film.colour(11, 10).y() - 1.).abs() < 1e-12);
+        assert_eq!(film.colour(9, 9).length(), 0.);
+    }
+}
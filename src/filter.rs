@@ -0,0 +1,153 @@
+use std::f64::consts::PI;
+
+#[derive(Copy, Clone, Debug)]
+pub enum FilterKind {
+    Box,
+    Tent,
+    Gaussian { alpha: f64 },
+    Mitchell { b: f64, c: f64 },
+    Lanczos { tau: f64 },
+}
+
+impl FilterKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "box" => Some(Self::Box),
+            "tent" => Some(Self::Tent),
+            "gaussian" => Some(Self::Gaussian { alpha: 2. }),
+            "mitchell" => Some(Self::Mitchell {
+                b: 1. / 3.,
+                c: 1. / 3.,
+            }),
+            "lanczos" => Some(Self::Lanczos { tau: 3. }),
+            _ => None,
+        }
+    }
+
+    pub fn default_radius(&self) -> f64 {
+        match self {
+            Self::Box => 0.5,
+            Self::Tent => 1.,
+            Self::Gaussian { .. } | Self::Mitchell { .. } => 2.,
+            Self::Lanczos { tau } => *tau,
+        }
+    }
+}
+
+// A separable pixel reconstruction filter. Offsets are measured in pixels from the pixel centre
+// and the filter is zero outside of `radius`.
+#[derive(Copy, Clone, Debug)]
+pub struct Filter {
+    pub kind: FilterKind,
+    pub radius: f64,
+}
+
+impl Filter {
+    pub fn from(kind: FilterKind, radius: f64) -> Self {
+        Self { kind, radius }
+    }
+
+    pub fn evaluate(&self, dx: f64, dy: f64) -> f64 {
+        if dx.abs() >= self.radius || dy.abs() >= self.radius {
+            return 0.;
+        }
+        self.evaluate_1d(dx) * self.evaluate_1d(dy)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let x = x.abs();
+        match self.kind {
+            FilterKind::Box => 1.,
+            FilterKind::Tent => self.radius - x,
+            FilterKind::Gaussian { alpha } => {
+                ((-alpha * x * x).exp() - (-alpha * self.radius * self.radius).exp()).max(0.)
+            }
+            FilterKind::Mitchell { b, c } => mitchell_1d(2. * x / self.radius, b, c),
+            // A sinc windowed by a sinc stretched over `tau` pixels, so it crosses zero at every
+            // whole pixel offset.
+            FilterKind::Lanczos { tau } => sinc(x) * sinc(x / tau),
+        }
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::from(FilterKind::Box, FilterKind::Box.default_radius())
+    }
+}
+
+fn mitchell_1d(x: f64, b: f64, c: f64) -> f64 {
+    if x > 1. {
+        ((-b - 6. * c) * x.powi(3)
+            + (6. * b + 30. * c) * x * x
+            + (-12. * b - 48. * c) * x
+            + (8. * b + 24. * c))
+            / 6.
+    } else {
+        ((12. - 9. * b - 6. * c) * x.powi(3) + (-18. + 12. * b + 6. * c) * x * x + (6. - 2. * b))
+            / 6.
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x < 1e-5 {
+        1.
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters() -> Vec<Filter> {
+        ["box", "tent", "gaussian", "mitchell", "lanczos"]
+            .iter()
+            .map(|name| {
+                let kind = FilterKind::from_name(name).unwrap();
+                Filter::from(kind, kind.default_radius())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lanczos_crosses_zero_at_whole_pixels() {
+        let kind = FilterKind::from_name("lanczos").unwrap();
+        let filter = Filter::from(kind, kind.default_radius());
+        assert_eq!(filter.radius, 3.);
+        for offset in [1., 2., -1., -2.] {
+            assert!(filter.evaluate(offset, 0.).abs() < 1e-12);
+            assert!(filter.evaluate(0., offset).abs() < 1e-12);
+        }
+        assert!(filter.evaluate(0.5, 0.) > 0.);
+        assert!(filter.evaluate(1.5, 0.) < 0.);
+    }
+
+    #[test]
+    fn is_zero_outside_the_radius() {
+        for filter in filters() {
+            let r = filter.radius;
+            for (dx, dy) in [(r, 0.), (0., -r), (r + 0.1, 0.3), (-0.2, r + 1.)] {
+                assert_eq!(filter.evaluate(dx, dy), 0., "{:?}", filter.kind);
+            }
+        }
+    }
+
+    #[test]
+    fn peaks_at_the_pixel_centre() {
+        for filter in filters() {
+            let peak = filter.evaluate(0., 0.);
+            assert!(peak > 0., "{:?}", filter.kind);
+            for i in 1..20 {
+                let offset = filter.radius * i as f64 / 20.;
+                assert!(filter.evaluate(offset, 0.) <= peak, "{:?}", filter.kind);
+                assert!(
+                    filter.evaluate(-offset, offset) <= peak,
+                    "{:?}",
+                    filter.kind
+                );
+            }
+        }
+    }
+}
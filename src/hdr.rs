@@ -0,0 +1,206 @@
+use crate::vec3::Colour;
+use std::{
+    fs::File,
+    io::{prelude::*, BufReader, BufWriter},
+};
+
+// A dynamically sized float image. Row 0 is the bottom of the image, matching both the film
+// layout and the row order of the PFM format.
+#[derive(Clone)]
+pub struct HdrImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Colour>,
+}
+
+impl HdrImage {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Colour::new(); width * height],
+        }
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> Colour {
+        self.pixels[j * self.width + i]
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, colour: Colour) {
+        self.pixels[j * self.width + i] = colour;
+    }
+
+    // Writes a colour ("PF") or single channel ("Pf") portable float map. Single channel images
+    // store the first component of each pixel.
+    pub fn write_pfm(&self, path: &str, greyscale: bool) {
+        let file = File::create(path).expect("Unable to create file");
+        let mut writer = BufWriter::new(file);
+        let magic = if greyscale { "Pf" } else { "PF" };
+        write!(writer, "{}\n{} {}\n-1.0\n", magic, self.width, self.height)
+            .expect("Unable to write to file");
+
+        for pixel in &self.pixels {
+            let channels = if greyscale { 1 } else { 3 };
+            for c in 0..channels {
+                writer
+                    .write_all(&(pixel[c] as f32).to_le_bytes())
+                    .expect("Unable to write to file");
+            }
+        }
+    }
+
+    pub fn read_pfm(path: &str) -> Result<Self, String> {
+        let mut reader = open(path)?;
+
+        let magic = read_token(&mut reader)?;
+        let channels = match magic.as_str() {
+            "PF" => 3,
+            "Pf" => 1,
+            _ => return Err(format!("{} is not a PFM file", path)),
+        };
+        let width: usize = parse_token(&mut reader)?;
+        let height: usize = parse_token(&mut reader)?;
+        let scale: f64 = parse_token(&mut reader)?;
+        let little_endian = scale < 0.;
+
+        let mut data = vec![0u8; width * height * channels * 4];
+        reader
+            .read_exact(&mut data)
+            .map_err(|_| format!("{} is missing pixel data", path))?;
+
+        let values: Vec<f64> = data
+            .chunks_exact(4)
+            .map(|bytes| {
+                let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+                if little_endian {
+                    f32::from_le_bytes(bytes) as f64
+                } else {
+                    f32::from_be_bytes(bytes) as f64
+                }
+            })
+            .collect();
+
+        let pixels = values
+            .chunks_exact(channels)
+            .map(|c| {
+                if channels == 1 {
+                    Colour::from(c[0], c[0], c[0])
+                } else {
+                    Colour::from(c[0], c[1], c[2])
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    // Reads a binary ("P6") or ASCII ("P3") PPM, scaling values into [0, 1]. No gamma decoding is
+    // applied.
+    pub fn read_ppm(path: &str) -> Result<Self, String> {
+        let mut reader = open(path)?;
+
+        let magic = read_token(&mut reader)?;
+        let width: usize = parse_token(&mut reader)?;
+        let height: usize = parse_token(&mut reader)?;
+        let max_value: f64 = parse_token(&mut reader)?;
+
+        let values: Vec<f64> = match magic.as_str() {
+            "P3" => (0..width * height * 3)
+                .map(|_| parse_token::<f64>(&mut reader))
+                .collect::<Result<_, _>>()?,
+            "P6" => {
+                let bytes_per_value = if max_value > 255. { 2 } else { 1 };
+                let mut data = vec![0u8; width * height * 3 * bytes_per_value];
+                reader
+                    .read_exact(&mut data)
+                    .map_err(|_| format!("{} is missing pixel data", path))?;
+                data.chunks_exact(bytes_per_value)
+                    .map(|bytes| match bytes {
+                        [value] => *value as f64,
+                        _ => u16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+                    })
+                    .collect()
+            }
+            _ => return Err(format!("{} is not a PPM file", path)),
+        };
+
+        // PPM rows run from the top of the image down.
+        let mut image = Self::new(width, height);
+        for (index, c) in values.chunks_exact(3).enumerate() {
+            let (i, j) = (index % width, height - 1 - index / width);
+            image.set(i, j, Colour::from(c[0], c[1], c[2]) / max_value);
+        }
+        Ok(image)
+    }
+
+    pub fn read(path: &str) -> Result<Self, String> {
+        if path.ends_with(".pfm") {
+            Self::read_pfm(path)
+        } else {
+            Self::read_ppm(path)
+        }
+    }
+
+    // Lookup with the image repeated every unit of `u` and `v`, for textures tiled across a
+    // surface.
+    pub fn sample_tiled(&self, u: f64, v: f64) -> Colour {
+        self.sample(u.rem_euclid(1.), v.rem_euclid(1.))
+    }
+
+    // Bilinearly filtered lookup with `u` and `v` in [0, 1], clamped at the edges.
+    pub fn sample(&self, u: f64, v: f64) -> Colour {
+        let x = (u * self.width as f64 - 0.5).clamp(0., (self.width - 1) as f64);
+        let y = (v * self.height as f64 - 0.5).clamp(0., (self.height - 1) as f64);
+        let (i, j) = (x as usize, y as usize);
+        let (i1, j1) = ((i + 1).min(self.width - 1), (j + 1).min(self.height - 1));
+        let (fx, fy) = (x - i as f64, y - j as f64);
+
+        let bottom = self.get(i, j) * (1. - fx) + self.get(i1, j) * fx;
+        let top = self.get(i, j1) * (1. - fx) + self.get(i1, j1) * fx;
+        bottom * (1. - fy) + top * fy
+    }
+}
+
+fn open(path: &str) -> Result<BufReader<File>, String> {
+    let file = File::open(path).map_err(|error| format!("Unable to open {}: {}", path, error))?;
+    Ok(BufReader::new(file))
+}
+
+// Reads a whitespace separated header token, consuming exactly one trailing whitespace byte so
+// binary data following the header is left untouched.
+pub fn read_token(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut token = String::new();
+    let mut byte = [0u8; 1];
+    loop {
+        if reader.read_exact(&mut byte).is_err() {
+            if token.is_empty() {
+                return Err("Unexpected end of file".to_string());
+            }
+            return Ok(token);
+        }
+        let c = byte[0] as char;
+        if c == '#' && token.is_empty() {
+            let mut comment = String::new();
+            reader
+                .read_line(&mut comment)
+                .map_err(|_| "Unexpected end of file".to_string())?;
+        } else if c.is_ascii_whitespace() {
+            if !token.is_empty() {
+                return Ok(token);
+            }
+        } else {
+            token.push(c);
+        }
+    }
+}
+
+pub fn parse_token<T: std::str::FromStr>(reader: &mut impl BufRead) -> Result<T, String> {
+    let token = read_token(reader)?;
+    token
+        .parse()
+        .map_err(|_| format!("Invalid header value: {}", token))
+}
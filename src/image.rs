@@ -1,4 +1,4 @@
-use crate::{IMAGE_HEIGHT, IMAGE_WIDTH};
+use crate::{colour, hdr::HdrImage, tonemap::ToneMapper};
 
 use std::{fs::File, io::prelude::*};
 #[derive(Copy, Clone)]
@@ -11,37 +11,101 @@ impl Pixel {
     fn new() -> Pixel {
         Self { r: 0, g: 0, b: 0 }
     }
+    #[allow(dead_code)]
     fn from(r: i32, g: i32, b: i32) -> Pixel {
         Self { r, g, b }
     }
 
-    fn to_tuple(&self) -> (i32, i32, i32) {
+    #[allow(dead_code)]
+    fn to_tuple(self) -> (i32, i32, i32) {
         (self.r, self.g, self.b)
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StereoLayout {
+    SideBySide,
+    OverUnder,
+    TwoFiles,
+}
+
+impl StereoLayout {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "side-by-side" => Some(Self::SideBySide),
+            "over-under" => Some(Self::OverUnder),
+            "two-files" => Some(Self::TwoFiles),
+            _ => None,
+        }
+    }
+}
+
 pub struct Image {
-    pub pixels: [[Pixel; IMAGE_WIDTH as usize]; IMAGE_HEIGHT as usize],
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Vec<Pixel>>,
 }
 impl Image {
-    pub fn new() -> Self {
+    pub fn new(width: usize, height: usize) -> Self {
         Self {
-            pixels: [[Pixel::new(); IMAGE_WIDTH as usize]; IMAGE_HEIGHT as usize],
+            width,
+            height,
+            pixels: vec![vec![Pixel::new(); width]; height],
         }
     }
 
+    pub fn from_hdr(hdr: &HdrImage, tone_mapper: &ToneMapper) -> Self {
+        let mut image = Self::new(hdr.width, hdr.height);
+        for j in 0..hdr.height {
+            for i in 0..hdr.width {
+                let (r, g, b) = colour::rescale_colour(hdr.get(i, j), 1, tone_mapper);
+                image.pixels[j][i] = Pixel { r, g, b };
+            }
+        }
+
+        image
+    }
+
     pub fn write_image(&self, mut file: File) {
-        write!(file, "P3\n{} {}\n255\n", IMAGE_WIDTH, IMAGE_HEIGHT)
-            .expect("Unable to write to file");
+        write!(file, "P3\n{} {}\n255\n", self.width, self.height).expect("Unable to write to file");
 
-        for i in (0..IMAGE_HEIGHT).rev() {
-            eprint!("\rWriting Image: {}/{} ", IMAGE_HEIGHT - i, IMAGE_HEIGHT);
-            for j in 0..IMAGE_WIDTH {
-                let pixel = self.pixels[i as usize][j as usize];
-                write!(file, "{} {} {}\n", pixel.r, pixel.g, pixel.b)
+        for i in (0..self.height).rev() {
+            eprint!("\rWriting Image: {}/{} ", self.height - i, self.height);
+            for j in 0..self.width {
+                let pixel = self.pixels[i][j];
+                writeln!(file, "{} {} {}", pixel.r, pixel.g, pixel.b)
                     .expect("Unable to write to file");
             }
         }
         eprintln!("\nDone.")
     }
+
+    fn write_row(&self, file: &mut File, row: usize) {
+        for pixel in self.pixels[row].iter() {
+            writeln!(file, "{} {} {}", pixel.r, pixel.g, pixel.b).expect("Unable to write to file");
+        }
+    }
+
+    // Writes a stereo pair as one image with the left eye on the left.
+    pub fn write_side_by_side(left: &Image, right: &Image, mut file: File) {
+        write!(file, "P3\n{} {}\n255\n", 2 * left.width, left.height)
+            .expect("Unable to write to file");
+
+        for i in (0..left.height).rev() {
+            left.write_row(&mut file, i);
+            right.write_row(&mut file, i);
+        }
+    }
+
+    // Writes a stereo pair as one image with the left eye on top.
+    pub fn write_over_under(left: &Image, right: &Image, mut file: File) {
+        write!(file, "P3\n{} {}\n255\n", left.width, 2 * left.height)
+            .expect("Unable to write to file");
+
+        for image in [left, right].iter() {
+            for i in (0..image.height).rev() {
+                image.write_row(&mut file, i);
+            }
+        }
+    }
 }
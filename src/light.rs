@@ -0,0 +1,61 @@
+use crate::{
+    objects::hittable::{HitRecord, Hittable},
+    ray::{Ray, RayKind},
+    spectrum::upsample,
+    vec3::{dot, Colour, Point3, Vec3},
+};
+use std::f64::consts::PI;
+
+// An infinitesimal light giving off `intensity` in every direction. Lights are numbered by their
+// position in the scene's list of lights, which is how objects are unlinked from them.
+#[derive(Copy, Clone, Debug)]
+pub struct PointLight {
+    pub position: Point3,
+    pub intensity: Colour,
+}
+
+impl PointLight {
+    pub fn from(position: Point3, intensity: Colour) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+// Light reaching a diffuse surface with reflectance `diffuse` directly from each linked light,
+// tested for occlusion with shadow rays. In spectral mode the result is at `wavelengths`.
+pub fn direct_lighting(
+    lights: &[PointLight],
+    world: &impl Hittable,
+    rec: &HitRecord,
+    diffuse: Colour,
+    wavelengths: Option<Vec3>,
+) -> Colour {
+    let mut colour = Colour::new();
+
+    for (i, light) in lights.iter().enumerate() {
+        if i < 64 && rec.light_mask & (1 << i) == 0 {
+            continue;
+        }
+
+        let to_light = light.position - rec.p;
+        let distance = to_light.length();
+        let direction = to_light / distance;
+        let cos_theta = dot(rec.normal, direction);
+        if cos_theta <= 0. || dot(rec.geometric_normal, direction) <= 0. {
+            continue;
+        }
+
+        let shadow_ray = Ray::from(rec.p, direction).with_kind(RayKind::Shadow);
+        if world.hit(shadow_ray, 0.001, distance, &mut HitRecord::new()) {
+            continue;
+        }
+
+        colour += diffuse
+            * upsample(light.intensity, wavelengths)
+            * (cos_theta / (PI * distance * distance));
+    }
+
+    colour
+}
@@ -1,21 +1,55 @@
+mod animation;
+mod aov;
 mod camera;
 mod colour;
+mod denoise;
+mod film;
+mod filter;
+mod hdr;
 mod image;
+mod light;
 mod material;
+mod medium;
 mod objects;
+mod options;
+mod perlin;
+mod polynomial;
 mod ray;
+mod scenes;
+mod spectrum;
+mod tonemap;
 mod utils;
 mod vec3;
-use camera::Camera;
-use image::{Image, Pixel};
-use material::material::{Dielectric, Lambertian, Material, Metal};
+use animation::{CameraAnimation, Interpolation, ObjectAnimation, Track};
+use aov::Aovs;
+use camera::{
+    camera::{Camera, CameraModel},
+    lens_system::LensSystem,
+    panoramic::{CubeMap, Equirectangular, Fisheye},
+    physical::PhysicalCamera,
+};
+use denoise::Denoiser;
+use film::Film;
+use hdr::HdrImage;
+use image::{Image, StereoLayout};
+use light::{direct_lighting, PointLight};
+use material::{
+    bump::Bumped,
+    cutout::{Cutout, OpacityMode},
+    material::{Lambertian, Material, Metal},
+};
 use objects::{
+    bvh::Bvh,
     hittable::{HitRecord, Hittable},
     hittable_list::HittableList,
     sphere::Sphere,
+    visibility::Visible,
 };
-use ray::Ray;
-use std::thread::{self, JoinHandle};
+use options::{CameraKind, Command, Options};
+use ray::{Ray, RayKind};
+use spectrum::{sample_wavelengths, upsample};
+use std::f64::consts::PI;
+use std::time::{Duration, Instant};
 use std::{fs::File, rc::Rc};
 use utils::{random_f64, INFINITY};
 use vec3::{unit_vector, Colour, Point3, Vec3};
@@ -26,7 +60,13 @@ const IMAGE_HEIGHT: i32 = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as i32;
 const SAMPLES_PER_PIXEL: i32 = 3;
 const MAX_DEPTH: i32 = 50;
 
-fn ray_colour(r: Ray, world: &impl Hittable, depth: i32) -> vec3::Colour {
+fn ray_colour(
+    mut r: Ray,
+    world: &impl Hittable,
+    lights: &[PointLight],
+    depth: i32,
+    first_hit: &mut Option<HitRecord>,
+) -> vec3::Colour {
     if depth <= 0 {
         return Colour::new();
     }
@@ -34,6 +74,34 @@ fn ray_colour(r: Ray, world: &impl Hittable, depth: i32) -> vec3::Colour {
     let mut rec = HitRecord::new();
 
     if world.hit(r, 0.001, INFINITY, &mut rec) {
+        // A ray inside a translucent object first walks through its medium, until it reaches the
+        // boundary that the rest of this function then handles.
+        let mut throughput = Colour::from(1., 1., 1.);
+        if !rec.front_face {
+            if let Some(medium) = rec.mat_ptr.medium(&rec) {
+                match medium.walk(world, &mut r, &mut rec) {
+                    Some(weight) => throughput = weight,
+                    None => return Colour::new(),
+                }
+            }
+        }
+        rec.mat_ptr.clone().perturb(r, &mut rec);
+
+        if first_hit.is_none() {
+            *first_hit = Some(rec.clone());
+        }
+
+        let direct = match rec.mat_ptr.diffuse(&rec) {
+            Some(diffuse) => direct_lighting(
+                lights,
+                world,
+                &rec,
+                upsample(diffuse, r.wavelengths),
+                r.wavelengths,
+            ),
+            None => Colour::new(),
+        };
+
         let mut scattered = Ray::new();
         let mut attenuation = Colour::new();
 
@@ -41,119 +109,526 @@ fn ray_colour(r: Ray, world: &impl Hittable, depth: i32) -> vec3::Colour {
             .mat_ptr
             .scatter(r, rec.clone(), &mut attenuation, &mut scattered)
         {
-            return attenuation * ray_colour(scattered, world, depth - 1);
+            if scattered.wavelengths.is_none() {
+                scattered.wavelengths = r.wavelengths;
+            }
+            return throughput
+                * (direct
+                    + attenuation * ray_colour(scattered, world, lights, depth - 1, &mut None));
         }
 
         let target = rec.p + rec.normal + Vec3::random_unit_vector();
-        return ray_colour(Ray::from(rec.p, target - rec.p), world, depth - 1) * 0.5;
+        return throughput
+            * (direct
+                + ray_colour(
+                    Ray {
+                        wavelengths: r.wavelengths,
+                        ..Ray::from(rec.p, target - rec.p).with_kind(RayKind::Diffuse)
+                    },
+                    world,
+                    lights,
+                    depth - 1,
+                    &mut None,
+                ) * 0.5);
     }
 
     let unit_direction = unit_vector(r.direction());
     let t = 0.5 * (unit_direction.y() + 1.0);
-    Colour::from(1.0, 1.0, 1.0) * (1.0 - t) + Colour::from(0.5, 0.7, 1.0) * t
+    let sky = Colour::from(1.0, 1.0, 1.0) * (1.0 - t) + Colour::from(0.5, 0.7, 1.0) * t;
+    upsample(sky, r.wavelengths)
+}
+
+// Traces one camera ray. In spectral mode the result is radiance at `wavelengths` rather than an
+// RGB colour.
+fn calculate_pixel_colour(
+    camera: &dyn CameraModel,
+    world: &impl Hittable,
+    lights: &[PointLight],
+    u: f64,
+    v: f64,
+    wavelengths: Option<Vec3>,
+) -> (Colour, Option<HitRecord>) {
+    let mut first_hit = None;
+    let colour = match camera.get_ray(u, v) {
+        Some(mut ray) => {
+            ray.wavelengths = wavelengths;
+            ray_colour(ray, world, lights, MAX_DEPTH, &mut first_hit) * camera.exposure()
+        }
+        None => Colour::new(),
+    };
+    (colour, first_hit)
 }
 
-fn calculate_pixel_colour(camera: &Camera, world: &impl Hittable, i: i32, j: i32) -> Colour {
-    let u = (i as f64 + random_f64(0., 1.)) / (IMAGE_WIDTH - 1) as f64;
-    let v = (j as f64 + random_f64(0., 1.)) / (IMAGE_HEIGHT - 1) as f64;
-    let ray = camera.get_ray(u, v);
+fn add_pixel_sample(
+    camera: &dyn CameraModel,
+    world: &impl Hittable,
+    options: &Options,
+    film: &mut Film,
+    aovs: &mut Option<Aovs>,
+    i: i32,
+    j: i32,
+) {
+    let x = i as f64 + random_f64(0., 1.);
+    let y = j as f64 + random_f64(0., 1.);
+    let u = x / (film.width - 1) as f64;
+    let v = y / (film.height - 1) as f64;
+    let first_hit = if options.spectral {
+        let wavelengths = sample_wavelengths();
+        let (radiance, first_hit) =
+            calculate_pixel_colour(camera, world, &options.lights, u, v, Some(wavelengths));
+        film.add_spectral_sample(x, y, radiance, wavelengths);
+        first_hit
+    } else {
+        let (colour, first_hit) =
+            calculate_pixel_colour(camera, world, &options.lights, u, v, None);
+        film.add_sample(x, y, colour);
+        first_hit
+    };
 
-    ray_colour(ray, world, MAX_DEPTH)
+    if let (Some(aovs), Some(hit)) = (aovs.as_mut(), first_hit) {
+        aovs.add_sample(i, j, &hit, camera.depth(hit.p));
+    }
 }
 
-fn create_image(camera: &Camera, world: &impl Hittable, samples_per_pixel: i32) -> Image {
-    let mut image = Image::new();
-    for row_num in 0..IMAGE_HEIGHT {
-        eprint!("\rRows remaining: {}/{} ", row_num, IMAGE_HEIGHT);
-        for pixel_num in 0..IMAGE_WIDTH {
-            let pixel_colour = (0..samples_per_pixel)
-                .map(|_| calculate_pixel_colour(&camera, world, pixel_num, row_num))
-                .sum();
+fn render(
+    camera: &dyn CameraModel,
+    world: &impl Hittable,
+    options: &Options,
+    film: &mut Film,
+    aovs: &mut Option<Aovs>,
+    samples_per_pixel: i32,
+) {
+    for row_num in 0..film.height {
+        eprint!("\rRows remaining: {}/{} ", row_num, film.height);
+        for pixel_num in 0..film.width {
+            for _ in 0..samples_per_pixel {
+                add_pixel_sample(camera, world, options, film, aovs, pixel_num, row_num);
+            }
+        }
+    }
+}
+
+// Renders whole-image passes of one sample per pixel until the time limit runs out. The first
+// pass always completes so every pixel has at least one sample; later passes may stop part way,
+// which is why each pixel is normalised by its own accumulated weight.
+fn render_timed(
+    camera: &dyn CameraModel,
+    world: &impl Hittable,
+    options: &Options,
+    film: &mut Film,
+    aovs: &mut Option<Aovs>,
+    time_limit: Duration,
+) {
+    let deadline = Instant::now() + time_limit;
+    let mut pass = 0;
 
-            let (r, g, b) = colour::rescale_colour(pixel_colour, samples_per_pixel);
-            image.pixels[row_num as usize][pixel_num as usize] = Pixel { r, g, b };
+    'passes: loop {
+        pass += 1;
+        for row_num in 0..film.height {
+            if pass > 1 && Instant::now() >= deadline {
+                break 'passes;
+            }
+            eprint!(
+                "\rPass {}, rows remaining: {}/{} ",
+                pass, row_num, film.height
+            );
+            for pixel_num in 0..film.width {
+                add_pixel_sample(camera, world, options, film, aovs, pixel_num, row_num);
+            }
         }
     }
+}
 
-    image
+// The ground is plain grey, with any maps given on the command line layered over it.
+fn ground_material(options: &Options) -> Result<Rc<dyn Material>, String> {
+    let mut ground: Rc<dyn Material> = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+    if let Some(path) = &options.normal_map {
+        ground = Rc::new(Bumped::normal_map(ground, path)?);
+    }
+    if let Some((path, scale)) = &options.bump_map {
+        ground = Rc::new(Bumped::bump_image(ground, path, *scale)?);
+    }
+    if let Some(path) = &options.opacity_map {
+        let mode = match options.opacity_threshold {
+            Some(threshold) => OpacityMode::Threshold(threshold),
+            None => OpacityMode::Stochastic,
+        };
+        ground = Rc::new(Cutout::image(ground, path, None, mode)?);
+    }
+    Ok(ground)
 }
 
-fn random_scene() -> HittableList {
+// Builds the chosen scene, hiding the objects given by `--hide` from some kinds of ray and
+// unlinking those given by `--unlink` from some lights. Objects are named by their IDs in the
+// object ID AOV.
+fn build_scene(options: &Options) -> Result<HittableList, String> {
     let mut world = HittableList::new();
-    let ground_material = Rc::from(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
-    world.add(Rc::new(Sphere::from(
-        Point3::from(0., -1000., 0.),
-        1000.,
-        ground_material,
-    )));
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_mat = random_f64(0., 1.);
-            let centre = Point3::from(
-                a as f64 + 0.9 * random_f64(0., 1.),
-                0.2,
-                b as f64 + 0.9 * random_f64(0., 1.),
-            );
+    let mut found = vec![];
+    for object in options.scene.build(ground_material(options)?)?.objects {
+        let id = world.object_count();
+        let hidden: Vec<_> = options.hidden.iter().filter(|h| h.0 == id).collect();
+        let unlinked: Vec<_> = options.unlinked.iter().filter(|u| u.0 == id).collect();
+        if hidden.is_empty() && unlinked.is_empty() {
+            world.add(object);
+            continue;
+        }
 
-            if (centre - Point3::from(4., 0.2, 0.)).length() > 0.9 {
-                let sphere_material: Rc<dyn Material>;
-
-                if choose_mat < 0.8 {
-                    let albedo = Colour::random(0., 1.) * Colour::random(0., 1.);
-                    sphere_material = Rc::new(Lambertian::from(albedo));
-                    world.add(Rc::new(Sphere::from(centre, 0.2, sphere_material)));
-                } else if choose_mat < 0.95 {
-                    let albedo = Colour::random(0.5, 1.);
-                    let fuzz = random_f64(0., 0.5);
-                    sphere_material = Rc::new(Metal::from(albedo, fuzz));
-                    world.add(Rc::new(Sphere::from(centre, 0.2, sphere_material)));
-                } else {
-                    sphere_material = Rc::new(Dielectric::from(1.5));
-                    world.add(Rc::new(Sphere::from(centre, 0.2, sphere_material)));
-                }
-            }
+        found.push(id);
+        let mut visible = Visible::from(object);
+        for &(_, kind) in hidden {
+            visible = visible.hidden_from(kind);
+        }
+        for &(_, light) in unlinked {
+            visible = visible.excluding_light(light);
+        }
+        world.add(Rc::new(visible));
+    }
+
+    let named = options.hidden.iter().map(|(id, _)| *id);
+    for id in named.chain(options.unlinked.iter().map(|(id, _)| *id)) {
+        if !found.contains(&id) {
+            return Err(format!("No object has ID {}", id));
         }
     }
+    Ok(world)
+}
 
-    let material1 = Rc::new(Dielectric::from(1.5));
-    world.add(Rc::new(Sphere::from(
-        Point3::from(0., 1., 0.),
-        1.0,
-        material1,
-    )));
+// A turntable around the scene, with a small metal sphere weaving between the others.
+fn scene_animation() -> (CameraAnimation, Vec<ObjectAnimation>) {
+    let mut position = Track::new(Interpolation::CatmullRom);
+    for key in 0..=8 {
+        let angle = key as f64 * PI / 4. + 0.23;
+        position.add(
+            key as f64 / 2.,
+            Point3::from(13.3 * angle.cos(), 2., 13.3 * angle.sin()),
+        );
+    }
 
-    let material2 = Rc::new(Lambertian::from(Colour::from(0.4, 0.2, 0.1)));
-    world.add(Rc::new(Sphere::from(
-        Point3::from(-4., 1., 0.),
-        1.0,
-        material2,
-    )));
+    let camera = CameraAnimation {
+        position,
+        look_at: Track::constant(Point3::from(0., 0., 0.)),
+        fov: Track::constant(20.),
+        aperture: Track::constant(0.1),
+    };
 
-    let material3 = Rc::new(Metal::from(Colour::from(0.7, 0.6, 0.5), 0.));
-    world.add(Rc::new(Sphere::from(
-        Point3::from(4., 1., 0.),
-        1.0,
-        material3,
-    )));
+    let sphere: Rc<dyn Hittable> = Rc::new(Sphere::from(
+        Point3::from(0., 0., 0.),
+        0.3,
+        Rc::new(Metal::from(Colour::from(0.9, 0.9, 0.9), 0.)),
+    ));
+    let mut weaving = ObjectAnimation::from(sphere);
+    weaving.translation = Track::new(Interpolation::CatmullRom)
+        .with_key(0., Vec3::from(-6., 0.3, 2.))
+        .with_key(1., Vec3::from(-2., 1.2, -2.))
+        .with_key(2., Vec3::from(2., 0.3, 2.))
+        .with_key(3., Vec3::from(6., 1.2, -2.))
+        .with_key(4., Vec3::from(-6., 0.3, 2.));
 
-    world
+    (camera, vec![weaving])
+}
+
+// Renders frames `first_frame` to `last_frame` of the scene animation to numbered files. The
+// static part of the scene is built once and shared by every frame.
+fn render_animation(options: &Options, first_frame: i32, last_frame: i32) {
+    let scene = build_scene(options).unwrap_or_else(|error| fail(&error));
+    let static_world: Rc<dyn Hittable> = Rc::new(Bvh::from(&scene));
+    let (camera_animation, object_animations) = scene_animation();
+    let (width, height) = options.resolution();
+
+    for frame in first_frame..=last_frame {
+        eprintln!("\nFrame {}/{}", frame, last_frame);
+        let time = frame as f64 / options.fps;
+
+        let mut world = HittableList::new();
+        world.add(static_world.clone());
+        for animation in &object_animations {
+            world.add(Rc::new(animation.transform_at(time)));
+        }
+
+        let camera = camera_animation
+            .camera_at(time, Vec3::from(0., 1., 0.), width as f64 / height as f64)
+            .with_aperture(options.aperture.clone())
+            .with_cat_eye(options.cat_eye);
+
+        let image = render_image(
+            &camera,
+            &world,
+            options,
+            &format!("result/frame_{:04}_", frame),
+        );
+        let file =
+            File::create(format!("result/frame_{:04}.ppm", frame)).expect("Unable to create file");
+        image.write_image(file);
+    }
+}
+
+// Renders one view, writing its HDR framebuffer and any AOVs to files starting with `prefix`,
+// and returns the tone mapped image.
+fn render_image(
+    camera: &dyn CameraModel,
+    world: &impl Hittable,
+    options: &Options,
+    prefix: &str,
+) -> Image {
+    let (width, height) = options.resolution();
+    let mut film = Film::new(width, height, options.filter);
+    // The denoiser is guided by the albedo and normal AOVs, so it needs them captured too.
+    let mut aovs = if options.aovs || options.denoise {
+        Some(Aovs::new(width, height))
+    } else {
+        None
+    };
+    match options.time_limit {
+        Some(time_limit) => render_timed(camera, world, options, &mut film, &mut aovs, time_limit),
+        None => render(
+            camera,
+            world,
+            options,
+            &mut film,
+            &mut aovs,
+            SAMPLES_PER_PIXEL,
+        ),
+    }
+
+    let mut hdr = film.to_hdr();
+    hdr.write_pfm(&format!("{}result.pfm", prefix), false);
+
+    if let Some(aovs) = &aovs {
+        if options.denoise {
+            let albedo = aovs.albedo_image();
+            let normal = aovs.normal_image();
+            hdr = Denoiser::new()
+                .denoise(&hdr, Some(&albedo), Some(&normal))
+                .unwrap_or_else(|error| fail(&error));
+            hdr.write_pfm(&format!("{}denoised.pfm", prefix), false);
+        }
+        if options.aovs {
+            aovs.write(prefix);
+        }
+    }
+
+    Image::from_hdr(&hdr, &options.tone_mapper)
+}
+
+fn denoise_file(
+    input: &str,
+    output: &str,
+    albedo: Option<&str>,
+    normal: Option<&str>,
+) -> Result<(), String> {
+    let beauty = HdrImage::read_pfm(input)?;
+    let albedo = albedo.map(HdrImage::read_pfm).transpose()?;
+    let normal = normal.map(HdrImage::read_pfm).transpose()?;
+
+    Denoiser::new()
+        .denoise(&beauty, albedo.as_ref(), normal.as_ref())?
+        .write_pfm(output, false);
+    Ok(())
+}
+
+// Reports an error in the user's input and exits.
+fn fail(error: &str) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
 }
 
 fn main() {
-    let world = random_scene();
-    let camera = Camera::from(
-        Point3::from(13., 2., 3.),
-        Point3::from(0., 0., 0.),
-        Point3::from(0., 1., 0.),
-        20.,
-        ASPECT_RATIO,
-        0.1,
-        10.,
-    );
+    let options = Options::from_args().unwrap_or_else(|error| fail(&error));
+    if let Command::Denoise {
+        input,
+        output,
+        albedo,
+        normal,
+    } = &options.command
+    {
+        denoise_file(input, output, albedo.as_deref(), normal.as_deref())
+            .unwrap_or_else(|error| fail(&error));
+        return;
+    }
+    if let Some((first_frame, last_frame)) = options.frames {
+        render_animation(&options, first_frame, last_frame);
+        return;
+    }
 
-    let file = File::create("result/result.ppm").expect("Unable to create file");
+    let world = Bvh::from(&build_scene(&options).unwrap_or_else(|error| fail(&error)));
+    let (width, height) = options.resolution();
+    let aspect_ratio = width as f64 / height as f64;
+    let look_from = Point3::from(13., 2., 3.);
+    let look_at = Point3::from(0., 0., 0.);
+    let v_up = Point3::from(0., 1., 0.);
+    let aperture = 0.1;
+    let focus_dist = 10.;
+    // Image position to auto-focus on, measured to the centre of the chosen pixel.
+    let focus_point = options.focus_pixel.map(|(i, j)| {
+        (
+            (i as f64 + 0.5) / (width - 1) as f64,
+            (j as f64 + 0.5) / (height - 1) as f64,
+        )
+    });
 
-    let image = create_image(&camera, &world, SAMPLES_PER_PIXEL);
-    image.write_image(file);
+    let perspective = match options.focal_length {
+        Some(focal_length) => {
+            let mut physical = PhysicalCamera {
+                look_from,
+                look_at,
+                v_up,
+                sensor_width: options.sensor_width,
+                sensor_height: options.sensor_width / aspect_ratio,
+                focal_length,
+                f_stop: options.f_stop,
+                focus_dist,
+                shutter_speed: options.shutter_speed,
+                iso: options.iso,
+                shift: options.shift,
+                tilt: (options.tilt, options.swing),
+            };
+            if let Some((s, t)) = focus_point {
+                physical.auto_focus(&world, s, t);
+            }
+            physical.build()
+        }
+        None => {
+            let thin_lens = |focus_dist| {
+                Camera::from(
+                    look_from,
+                    look_at,
+                    v_up,
+                    options.fov.unwrap_or(20.),
+                    aspect_ratio,
+                    aperture,
+                    focus_dist,
+                )
+                .with_shift(options.shift.0, options.shift.1)
+                .with_tilt(options.tilt, options.swing)
+            };
+            // Shift reframes the image, so the focus pixel is looked up in the shifted view.
+            let focus_dist = focus_point
+                .and_then(|(s, t)| thin_lens(focus_dist).depth_at(&world, s, t))
+                .unwrap_or(focus_dist);
+            thin_lens(focus_dist)
+        }
+    }
+    .with_aperture(options.aperture.clone())
+    .with_cat_eye(options.cat_eye);
+
+    let camera: Box<dyn CameraModel> = match &options.camera {
+        CameraKind::Perspective => Box::new(perspective.clone()),
+        CameraKind::Orthographic => Box::new(
+            Camera::orthographic(
+                look_from,
+                look_at,
+                v_up,
+                options.view_height * aspect_ratio,
+                options.view_height,
+                aperture,
+                focus_dist,
+            )
+            .with_aperture(options.aperture.clone())
+            .with_cat_eye(options.cat_eye)
+            .with_shift(options.shift.0, options.shift.1)
+            .with_tilt(options.tilt, options.swing),
+        ),
+        CameraKind::Equirectangular => Box::new(Equirectangular::from(look_from, look_at, v_up)),
+        CameraKind::Fisheye(mapping) => Box::new(Fisheye::from(
+            look_from,
+            look_at,
+            v_up,
+            *mapping,
+            options.fov.unwrap_or(180.),
+            aspect_ratio,
+        )),
+        CameraKind::CubeMap => Box::new(CubeMap::from(look_from, look_at, v_up)),
+        CameraKind::LensSystem(elements) => Box::new(
+            LensSystem::from(
+                look_from,
+                look_at,
+                v_up,
+                elements.clone(),
+                options.sensor_width,
+                aspect_ratio,
+                focus_dist,
+            )
+            .unwrap_or_else(|error| fail(&error)),
+        ),
+    };
+
+    match options.stereo {
+        None => {
+            let image = render_image(camera.as_ref(), &world, &options, "result/");
+            let file = File::create("result/result.ppm").expect("Unable to create file");
+            image.write_image(file);
+        }
+        Some(layout) => {
+            let (left_camera, right_camera) = perspective.stereo_pair(
+                options.eye_separation,
+                options.convergence_dist.unwrap_or(perspective.focus_dist()),
+            );
+            let left = render_image(&left_camera, &world, &options, "result/left_");
+            let right = render_image(&right_camera, &world, &options, "result/right_");
+
+            match layout {
+                StereoLayout::SideBySide => {
+                    let file = File::create("result/result.ppm").expect("Unable to create file");
+                    Image::write_side_by_side(&left, &right, file);
+                }
+                StereoLayout::OverUnder => {
+                    let file = File::create("result/result.ppm").expect("Unable to create file");
+                    Image::write_over_under(&left, &right, file);
+                }
+                StereoLayout::TwoFiles => {
+                    let file =
+                        File::create("result/left_result.ppm").expect("Unable to create file");
+                    left.write_image(file);
+                    let file =
+                        File::create("result/right_result.ppm").expect("Unable to create file");
+                    right.write_image(file);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use objects::plane::Plane;
+    use ray::RayKind;
+    use vec3::dot;
+
+    // Scatters every ray straight back through the surface, as a badly bent shading normal
+    // would.
+    struct ThroughSurface;
+
+    impl Material for ThroughSurface {
+        fn scatter(
+            &self,
+            _r_in: Ray,
+            rec: HitRecord,
+            attenuation: &mut Colour,
+            scattered: &mut Ray,
+        ) -> bool {
+            *scattered = Ray::from(rec.p, -rec.geometric_normal).with_kind(RayKind::Diffuse);
+            *attenuation = Colour::from(1., 1., 1.);
+            true
+        }
+    }
+
+    #[test]
+    fn absorbs_rays_scattered_below_the_geometric_surface() {
+        let bumped = Bumped::bump(Rc::new(ThroughSurface), Rc::new(|u, _| 0.5 * u));
+        let mut world = HittableList::new();
+        world.add(Rc::new(Plane::from(
+            Point3::new(),
+            Vec3::from(0., 1., 0.),
+            Rc::new(bumped),
+        )));
+
+        let r = Ray::from(Point3::from(0., 1., 0.), Vec3::from(0.3, -1., 0.2));
+        let mut first_hit = None;
+        let colour = ray_colour(r, &world, &[], MAX_DEPTH, &mut first_hit);
+
+        let hit = first_hit.unwrap();
+        assert!(dot(hit.normal, hit.geometric_normal) < 0.99);
+        assert_eq!(colour.length(), 0.);
+    }
 }
@@ -0,0 +1,206 @@
+use crate::{
+    hdr::HdrImage,
+    material::material::Material,
+    medium::Medium,
+    objects::hittable::HitRecord,
+    ray::{Ray, RayKind},
+    tonemap::luminance,
+    vec3::{cross, dot, Colour, Vec3},
+};
+use std::rc::Rc;
+
+// Step in surface coordinates used to differentiate height functions.
+const DELTA: f64 = 1e-3;
+
+pub enum Perturbation {
+    // An image of tangent space normals, with red along the surface's u direction, green along
+    // v and blue out of the surface, each mapped from [-1, 1] to [0, 1].
+    NormalMap(Rc<HdrImage>),
+    // A height field over the surface coordinates, in world units.
+    Bump(Rc<dyn Fn(f64, f64) -> f64>),
+}
+
+// Adds fine detail to another material by changing its shading normal, without changing the
+// geometry. The geometric normal is kept so that light can't leak through the surface: paths
+// arriving from behind the shading normal have it bent back towards the geometric normal, and
+// scattered rays that end up on the wrong side of the real surface are absorbed.
+pub struct Bumped {
+    pub base: Rc<dyn Material>,
+    pub perturbation: Perturbation,
+}
+
+impl Bumped {
+    pub fn normal_map(base: Rc<dyn Material>, path: &str) -> Result<Self, String> {
+        Ok(Self {
+            base,
+            perturbation: Perturbation::NormalMap(Rc::new(HdrImage::read(path)?)),
+        })
+    }
+
+    pub fn bump(base: Rc<dyn Material>, height: Rc<dyn Fn(f64, f64) -> f64>) -> Self {
+        Self {
+            base,
+            perturbation: Perturbation::Bump(height),
+        }
+    }
+
+    // Uses the brightness of an image as a height field, scaled so white is `scale` world units
+    // high.
+    pub fn bump_image(base: Rc<dyn Material>, path: &str, scale: f64) -> Result<Self, String> {
+        let image = HdrImage::read(path)?;
+        Ok(Self::bump(
+            base,
+            Rc::new(move |u, v| luminance(image.sample_tiled(u, v)) * scale),
+        ))
+    }
+
+    fn shading_normal(&self, rec: &HitRecord) -> Option<Vec3> {
+        let n = rec.normal;
+        let dpdu = rec.dpdu - n * dot(n, rec.dpdu);
+        if dpdu.near_zero() {
+            return None;
+        }
+
+        match &self.perturbation {
+            Perturbation::NormalMap(image) => {
+                let t = dpdu.unit_vector();
+                let b = cross(n, t);
+                let c = image.sample_tiled(rec.u, rec.v) * 2. - Colour::from(1., 1., 1.);
+                Some(t * c.x() + b * c.y() + n * c.z())
+            }
+            Perturbation::Bump(height) => {
+                // Moving the surface along its normal by the height changes its tangents by the
+                // height's derivatives along the normal.
+                let h = height(rec.u, rec.v);
+                let dhdu = (height(rec.u + DELTA, rec.v) - h) / DELTA;
+                let dhdv = (height(rec.u, rec.v + DELTA) - h) / DELTA;
+                let shading = cross(rec.dpdu + n * dhdu, rec.dpdv + n * dhdv);
+                if shading.near_zero() {
+                    return None;
+                }
+                Some(if dot(shading, n) < 0. {
+                    -shading
+                } else {
+                    shading
+                })
+            }
+        }
+    }
+}
+
+impl Material for Bumped {
+    fn accepts_hit(&self, rec: &HitRecord) -> bool {
+        self.base.accepts_hit(rec)
+    }
+
+    fn perturb(&self, r_in: Ray, rec: &mut HitRecord) {
+        self.base.perturb(r_in, rec);
+        let mut shading = match self.shading_normal(rec) {
+            Some(shading) => shading.unit_vector(),
+            None => return,
+        };
+
+        // Keep the incoming direction in front of the shading normal.
+        let wo = -r_in.direction().unit_vector();
+        let cos = dot(wo, shading);
+        let minimum = 0.01;
+        if cos < minimum {
+            shading = (shading + wo * (minimum - cos)).unit_vector();
+        }
+        rec.normal = shading;
+    }
+
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: HitRecord,
+        attenuation: &mut Colour,
+        scattered: &mut Ray,
+    ) -> bool {
+        let geometric_normal = rec.geometric_normal;
+        if !self.base.scatter(r_in, rec, attenuation, scattered) {
+            return false;
+        }
+        // A perturbed normal can send the ray out of the wrong side of the geometric surface,
+        // so it's absorbed there rather than left to leak through.
+        let transmitted = scattered.kind == RayKind::Refraction;
+        if (dot(scattered.direction(), geometric_normal) > 0.) == transmitted {
+            *attenuation = Colour::new();
+        }
+        true
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Colour {
+        self.base.albedo(rec)
+    }
+
+    fn diffuse(&self, rec: &HitRecord) -> Option<Colour> {
+        self.base.diffuse(rec)
+    }
+
+    fn medium(&self, rec: &HitRecord) -> Option<Medium> {
+        self.base.medium(rec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::{layered::Layered, material::Lambertian, mix::Mix, thin_film::ThinFilm},
+        vec3::Point3,
+    };
+
+    fn flat_hit() -> HitRecord {
+        let mut rec = HitRecord::new();
+        rec.normal = Vec3::from(0., 1., 0.);
+        rec.geometric_normal = rec.normal;
+        rec.dpdu = Vec3::from(1., 0., 0.);
+        rec.dpdv = Vec3::from(0., 0., 1.);
+        rec
+    }
+
+    fn perturbed_normal(material: &dyn Material) -> Vec3 {
+        let mut rec = flat_hit();
+        material.perturb(
+            Ray::from(Point3::from(0., 1., 0.), Vec3::from(0., -1., 0.)),
+            &mut rec,
+        );
+        rec.normal
+    }
+
+    #[test]
+    fn wrapper_materials_keep_the_bump() {
+        let plain = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        let bumped: Rc<dyn Material> =
+            Rc::new(Bumped::bump(plain.clone(), Rc::new(|u, _| 0.5 * u)));
+        let expected = perturbed_normal(bumped.as_ref());
+        assert!(dot(expected, Vec3::from(0., 1., 0.)) < 0.95);
+
+        let wrapped: Vec<Rc<dyn Material>> = vec![
+            Rc::new(Layered::from(plain.clone(), bumped.clone(), 1.5)),
+            Rc::new(Mix::constant(plain, bumped.clone(), 1.)),
+            Rc::new(ThinFilm::uniform(bumped, 300., 1.33, (1.5, 0.))),
+        ];
+        for material in wrapped {
+            assert!((perturbed_normal(material.as_ref()) - expected).length() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn absorbs_rays_scattered_through_the_geometric_surface() {
+        let white = Colour::from(1., 1., 1.);
+        let bumped = Bumped::bump(Rc::new(Lambertian::from(white)), Rc::new(|u, _| 0.5 * u));
+        let r_in = Ray::from(Point3::from(0., 1., 0.), Vec3::from(0., -1., 0.));
+
+        for _ in 0..100 {
+            let mut rec = flat_hit();
+            bumped.perturb(r_in, &mut rec);
+            let mut attenuation = Colour::new();
+            let mut scattered = Ray::from(Point3::new(), Vec3::new());
+            assert!(bumped.scatter(r_in, rec.clone(), &mut attenuation, &mut scattered));
+            let above = dot(scattered.direction(), rec.geometric_normal) > 0.;
+            assert_eq!(attenuation.length() > 0., above);
+        }
+    }
+}
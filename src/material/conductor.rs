@@ -0,0 +1,175 @@
+use crate::{
+    material::material::Material,
+    objects::hittable::HitRecord,
+    ray::{Ray, RayKind},
+    spectrum::{shading_wavelengths, RGB_WAVELENGTHS},
+    vec3::{dot, reflect, Colour, Vec3},
+};
+
+// Measured complex index of refraction as (wavelength in nm, n, k), interpolated linearly.
+// Values are rounded from Johnson and Christy (1972) for the noble metals and Rakić (1995) for
+// aluminium.
+pub type IorTable = &'static [(f64, f64, f64)];
+
+pub const GOLD: IorTable = &[
+    (400., 1.66, 1.96),
+    (450., 1.50, 1.88),
+    (500., 0.97, 1.87),
+    (550., 0.43, 2.45),
+    (600., 0.25, 2.98),
+    (650., 0.17, 3.15),
+    (700., 0.16, 3.80),
+];
+pub const COPPER: IorTable = &[
+    (400., 1.18, 2.21),
+    (450., 1.24, 2.40),
+    (500., 1.12, 2.56),
+    (550., 1.02, 2.58),
+    (600., 0.27, 3.41),
+    (650., 0.21, 3.67),
+    (700., 0.21, 4.20),
+];
+pub const SILVER: IorTable = &[
+    (400., 0.17, 1.95),
+    (450., 0.14, 2.55),
+    (500., 0.13, 3.00),
+    (550., 0.12, 3.35),
+    (600., 0.12, 3.70),
+    (650., 0.14, 4.10),
+    (700., 0.14, 4.50),
+];
+pub const ALUMINIUM: IorTable = &[
+    (400., 0.49, 4.86),
+    (450., 0.62, 5.47),
+    (500., 0.77, 6.08),
+    (550., 0.96, 6.69),
+    (600., 1.20, 7.26),
+    (650., 1.47, 7.79),
+    (700., 1.83, 8.31),
+];
+
+// A metal whose colour comes from the Fresnel equations for its measured complex index of
+// refraction.
+#[derive(Copy, Clone)]
+pub struct Conductor {
+    pub ior: IorTable,
+    pub fuzz: f64,
+}
+
+impl Conductor {
+    pub fn from(ior: IorTable, fuzz: f64) -> Self {
+        Self {
+            ior,
+            fuzz: fuzz.min(1.),
+        }
+    }
+
+    pub fn from_name(name: &str, fuzz: f64) -> Option<Self> {
+        let ior = match name {
+            "gold" => GOLD,
+            "copper" => COPPER,
+            "silver" => SILVER,
+            "aluminium" | "aluminum" => ALUMINIUM,
+            _ => return None,
+        };
+        Some(Self::from(ior, fuzz))
+    }
+
+    fn ior_at(&self, lambda: f64) -> (f64, f64) {
+        let table = self.ior;
+        let (first, last) = (table[0], table[table.len() - 1]);
+        if lambda <= first.0 {
+            return (first.1, first.2);
+        }
+        if lambda >= last.0 {
+            return (last.1, last.2);
+        }
+
+        let i = table.iter().take_while(|e| e.0 <= lambda).count() - 1;
+        let (a, b) = (table[i], table[i + 1]);
+        let u = (lambda - a.0) / (b.0 - a.0);
+        (a.1 + (b.1 - a.1) * u, a.2 + (b.2 - a.2) * u)
+    }
+
+    // Unpolarised Fresnel reflectance of the metal from air.
+    fn reflectance(&self, cos_i: f64, lambda: f64) -> f64 {
+        let (n, k) = self.ior_at(lambda);
+        let cos2 = cos_i * cos_i;
+        let sin2 = 1. - cos2;
+
+        let t0 = n * n - k * k - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4. * n * n * k * k).sqrt();
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.).sqrt();
+
+        let t1 = a2_plus_b2 + cos2;
+        let t2 = 2. * a * cos_i;
+        let rs = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+
+        0.5 * (rs + rp)
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: HitRecord,
+        attenuation: &mut Colour,
+        scattered: &mut Ray,
+    ) -> bool {
+        let unit_direction = r_in.direction().unit_vector();
+        let reflected = reflect(unit_direction, rec.normal);
+        *scattered = Ray::from(rec.p, reflected + Vec3::random_unit_vector() * self.fuzz)
+            .with_kind(RayKind::Reflection);
+
+        let cos_i = dot(-unit_direction, rec.normal).clamp(0., 1.);
+        let lambda = shading_wavelengths(r_in.wavelengths);
+        *attenuation = Colour::from(
+            self.reflectance(cos_i, lambda.x()),
+            self.reflectance(cos_i, lambda.y()),
+            self.reflectance(cos_i, lambda.z()),
+        );
+
+        dot(scattered.direction(), rec.normal) > 0.
+    }
+
+    fn albedo(&self, _rec: &HitRecord) -> Colour {
+        let lambda = RGB_WAVELENGTHS;
+        Colour::from(
+            self.reflectance(1., lambda[0]),
+            self.reflectance(1., lambda[1]),
+            self.reflectance(1., lambda[2]),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gold_reflects_like_its_index_at_normal_incidence() {
+        let gold = Conductor::from(GOLD, 0.);
+        for &(lambda, n, k) in GOLD {
+            let expected = ((n - 1.) * (n - 1.) + k * k) / ((n + 1.) * (n + 1.) + k * k);
+            assert!((gold.reflectance(1., lambda) - expected).abs() < 1e-12);
+        }
+        assert!((gold.reflectance(1., 550.) - 0.7863).abs() < 1e-4);
+
+        // Gold is yellow: it reflects red and green far more than blue.
+        let albedo = gold.albedo(&HitRecord::new());
+        assert!(albedo.x() > albedo.y() && albedo.y() > albedo.z() + 0.2);
+    }
+
+    #[test]
+    fn reflects_everything_at_grazing_incidence() {
+        for metal in ["gold", "copper", "silver", "aluminium"] {
+            let conductor = Conductor::from_name(metal, 0.).unwrap();
+            assert!(conductor.reflectance(1e-9, 550.) > 0.999);
+        }
+    }
+}
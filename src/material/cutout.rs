@@ -0,0 +1,147 @@
+use crate::{
+    hdr::HdrImage, material::material::Material, medium::Medium, objects::hittable::HitRecord,
+    ray::Ray, tonemap::luminance, utils::random_f64, vec3::Colour,
+};
+use std::rc::Rc;
+
+pub enum Opacity {
+    Constant(f64),
+    // An image over the surface coordinates, read from one channel (0 to 2 for red, green and
+    // blue) or from its brightness when `channel` is `None`.
+    Image {
+        image: Rc<HdrImage>,
+        channel: Option<usize>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OpacityMode {
+    // Hits count with probability equal to the opacity, so partly transparent regions converge
+    // to the right blend.
+    Stochastic,
+    // Hits count where the opacity is at least the threshold, giving hard edges.
+    Threshold(f64),
+}
+
+// Makes parts of another material's surface transparent, for leaves, fences and decals modelled
+// as simple shapes. Rays pass straight through the transparent parts as if nothing was there.
+pub struct Cutout {
+    pub base: Rc<dyn Material>,
+    pub opacity: Opacity,
+    pub mode: OpacityMode,
+}
+
+impl Cutout {
+    pub fn from(base: Rc<dyn Material>, opacity: Opacity, mode: OpacityMode) -> Self {
+        Self {
+            base,
+            opacity,
+            mode,
+        }
+    }
+
+    pub fn image(
+        base: Rc<dyn Material>,
+        path: &str,
+        channel: Option<usize>,
+        mode: OpacityMode,
+    ) -> Result<Self, String> {
+        let image = Rc::new(HdrImage::read(path)?);
+        Ok(Self::from(base, Opacity::Image { image, channel }, mode))
+    }
+
+    pub fn opacity_at(&self, rec: &HitRecord) -> f64 {
+        match &self.opacity {
+            Opacity::Constant(opacity) => *opacity,
+            Opacity::Image { image, channel } => {
+                let colour = image.sample_tiled(rec.u, rec.v);
+                match channel {
+                    Some(channel) => colour[*channel],
+                    None => luminance(colour),
+                }
+            }
+        }
+    }
+}
+
+impl Material for Cutout {
+    fn accepts_hit(&self, rec: &HitRecord) -> bool {
+        let opacity = self.opacity_at(rec);
+        let visible = match self.mode {
+            OpacityMode::Stochastic => random_f64(0., 1.) < opacity,
+            OpacityMode::Threshold(threshold) => opacity >= threshold,
+        };
+        visible && self.base.accepts_hit(rec)
+    }
+
+    fn perturb(&self, r_in: Ray, rec: &mut HitRecord) {
+        self.base.perturb(r_in, rec);
+    }
+
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: HitRecord,
+        attenuation: &mut Colour,
+        scattered: &mut Ray,
+    ) -> bool {
+        self.base.scatter(r_in, rec, attenuation, scattered)
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Colour {
+        self.base.albedo(rec)
+    }
+
+    fn diffuse(&self, rec: &HitRecord) -> Option<Colour> {
+        self.base.diffuse(rec)
+    }
+
+    fn medium(&self, rec: &HitRecord) -> Option<Medium> {
+        self.base.medium(rec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{
+        layered::Layered, material::Lambertian, mix::Mix, subsurface::Subsurface,
+        thin_film::ThinFilm,
+    };
+
+    fn transparent() -> Rc<dyn Material> {
+        let base = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        Rc::new(Cutout::from(
+            base,
+            Opacity::Constant(0.),
+            OpacityMode::Threshold(0.5),
+        ))
+    }
+
+    #[test]
+    fn wrapped_cutouts_stay_transparent() {
+        let opaque = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        let wrapped: Vec<Rc<dyn Material>> = vec![
+            Rc::new(Layered::from(opaque.clone(), transparent(), 1.5)),
+            Rc::new(Mix::constant(opaque.clone(), transparent(), 1.)),
+            Rc::new(Mix::constant(transparent(), opaque, 0.)),
+            Rc::new(ThinFilm::uniform(transparent(), 300., 1.33, (1.5, 0.))),
+        ];
+        let rec = HitRecord::new();
+        for material in wrapped {
+            assert!(!material.accepts_hit(&rec));
+        }
+    }
+
+    #[test]
+    fn forwards_the_base_medium() {
+        let skin = Subsurface::from(Colour::from(0.9, 0.6, 0.5), Colour::from(1., 0.5, 0.2), 1.4);
+        let cutout = Cutout::from(
+            Rc::new(skin),
+            Opacity::Constant(1.),
+            OpacityMode::Stochastic,
+        );
+        let medium = cutout.medium(&HitRecord::new()).unwrap();
+        assert_eq!(medium.mean_free_path.y(), 0.5);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    material::material::{Dielectric, Material},
+    medium::Medium,
+    objects::hittable::HitRecord,
+    ray::{Ray, RayKind},
+    utils::random_f64,
+    vec3::{dot, reflect, Colour},
+};
+use std::rc::Rc;
+
+// A material under a thin transparent coating, such as a clear coat over paint or varnish over
+// metal. `top` describes the light reflected by the coating's surface and `bottom` the material
+// under it. Each path picks one of them stochastically: the coating's surface with the Fresnel
+// reflectance of a boundary with index `ior`, otherwise the base, whose light then loses what
+// the coating reflects back in on the way out. Only the coating's reflected lobe is taken from
+// `top`: whatever `top` would send below the surface, such as a dielectric's refraction, is
+// replaced by the mirror reflection tinted by `top`'s attenuation.
+pub struct Layered {
+    pub top: Rc<dyn Material>,
+    pub bottom: Rc<dyn Material>,
+    pub ior: f64,
+}
+
+impl Layered {
+    pub fn from(top: Rc<dyn Material>, bottom: Rc<dyn Material>, ior: f64) -> Self {
+        Self { top, bottom, ior }
+    }
+
+    fn fresnel(&self, cosine: f64) -> f64 {
+        Dielectric::reflectance(cosine.clamp(0., 1.), 1. / self.ior)
+    }
+}
+
+impl Material for Layered {
+    // The coating only covers the parts of the surface where the base is.
+    fn accepts_hit(&self, rec: &HitRecord) -> bool {
+        self.bottom.accepts_hit(rec) && self.top.accepts_hit(rec)
+    }
+
+    // The coating follows the base, so it picks up the base's perturbation before its own.
+    fn perturb(&self, r_in: Ray, rec: &mut HitRecord) {
+        self.bottom.perturb(r_in, rec);
+        self.top.perturb(r_in, rec);
+    }
+
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: HitRecord,
+        attenuation: &mut Colour,
+        scattered: &mut Ray,
+    ) -> bool {
+        let cos_in = dot(-r_in.direction().unit_vector(), rec.normal);
+        if random_f64(0., 1.) < self.fresnel(cos_in) {
+            let (p, normal) = (rec.p, rec.normal);
+            if !self.top.scatter(r_in, rec, attenuation, scattered) {
+                return false;
+            }
+            if dot(scattered.direction(), normal) <= 0. {
+                let mirrored = reflect(r_in.direction().unit_vector(), normal);
+                *scattered = Ray::from(p, mirrored).with_kind(RayKind::Reflection);
+            }
+            return true;
+        }
+
+        let normal = rec.normal;
+        if !self.bottom.scatter(r_in, rec, attenuation, scattered) {
+            return false;
+        }
+        let cos_out = dot(scattered.direction().unit_vector(), normal);
+        *attenuation *= 1. - self.fresnel(cos_out);
+        true
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Colour {
+        self.bottom.albedo(rec)
+    }
+
+    // Light from the scene's lights is treated as arriving and leaving at normal incidence.
+    fn diffuse(&self, rec: &HitRecord) -> Option<Colour> {
+        let transmission = 1. - self.fresnel(1.);
+        self.bottom
+            .diffuse(rec)
+            .map(|diffuse| diffuse * (transmission * transmission))
+    }
+
+    fn medium(&self, rec: &HitRecord) -> Option<Medium> {
+        self.bottom.medium(rec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::Lambertian,
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn dielectric_coat_reflects_above_the_surface() {
+        let layered = Layered::from(
+            Rc::new(Dielectric::from(1.5)),
+            Rc::new(Lambertian::from(Colour::from(0.8, 0.1, 0.1))),
+            1.5,
+        );
+
+        let mut rec = HitRecord::new();
+        rec.normal = Vec3::from(0., 0., 1.);
+        rec.front_face = true;
+        // A grazing ray reflects off the coat most of the time.
+        let r_in = Ray::from(Point3::from(-1., 0., 0.05), Vec3::from(1., 0., -0.05));
+
+        for _ in 0..1000 {
+            let mut attenuation = Colour::new();
+            let mut scattered = Ray::from(Point3::new(), Vec3::new());
+            assert!(layered.scatter(r_in, rec.clone(), &mut attenuation, &mut scattered));
+            assert!(dot(scattered.direction(), rec.normal) > 0.);
+        }
+    }
+}
@@ -1,11 +1,22 @@
 use crate::{
+    medium::Medium,
     objects::hittable::HitRecord,
-    ray::Ray,
+    ray::{Ray, RayKind},
+    spectrum::upsample,
     utils::random_f64,
     vec3::{dot, reflect, refract, Colour, Vec3},
 };
 
 pub trait Material {
+    // Called by shapes for each intersection they find, which only counts as a hit if this
+    // returns true. Cutout materials use it to let rays through transparent parts of a surface.
+    fn accepts_hit(&self, _rec: &HitRecord) -> bool {
+        true
+    }
+
+    // Changes the shading normal at a hit before it is shaded, keeping the geometric normal.
+    fn perturb(&self, _r_in: Ray, _rec: &mut HitRecord) {}
+
     fn scatter(
         &self,
         r_in: Ray,
@@ -13,6 +24,21 @@ pub trait Material {
         attenuation: &mut Colour,
         scattered: &mut Ray,
     ) -> bool;
+
+    // The surface colour at a hit, used for the albedo AOV.
+    fn albedo(&self, _rec: &HitRecord) -> Colour {
+        Colour::from(1., 1., 1.)
+    }
+
+    // Reflectance of the part of the surface lit directly by the scene's lights, if any.
+    fn diffuse(&self, _rec: &HitRecord) -> Option<Colour> {
+        None
+    }
+
+    // The medium filling the object, for rays travelling inside it that hit its surface at `rec`.
+    fn medium(&self, _rec: &HitRecord) -> Option<Medium> {
+        None
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -27,7 +53,7 @@ impl Lambertian {
 impl Material for Lambertian {
     fn scatter(
         &self,
-        _r_in: Ray,
+        r_in: Ray,
         rec: HitRecord,
         attenuation: &mut Colour,
         scattered: &mut Ray,
@@ -38,10 +64,18 @@ impl Material for Lambertian {
             scatter_direction = rec.normal;
         }
 
-        *scattered = Ray::from(rec.p, scatter_direction);
-        *attenuation = self.albedo;
+        *scattered = Ray::from(rec.p, scatter_direction).with_kind(RayKind::Diffuse);
+        *attenuation = upsample(self.albedo, r_in.wavelengths);
         true
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Colour {
+        self.albedo
+    }
+
+    fn diffuse(&self, _rec: &HitRecord) -> Option<Colour> {
+        Some(self.albedo)
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -66,24 +100,89 @@ impl Material for Metal {
         scattered: &mut Ray,
     ) -> bool {
         let reflected = reflect(r_in.direction().unit_vector(), rec.normal);
-        *scattered = Ray::from(rec.p, reflected + Vec3::random_unit_vector() * self.fuzz);
-        *attenuation = self.albedo;
+        *scattered = Ray::from(rec.p, reflected + Vec3::random_unit_vector() * self.fuzz)
+            .with_kind(RayKind::Reflection);
+        *attenuation = upsample(self.albedo, r_in.wavelengths);
         dot(scattered.direction(), rec.normal) > 0.
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Colour {
+        self.albedo
+    }
+}
+
+// Index of refraction, either constant or varying with wavelength. Wavelengths are in nanometres
+// and the dispersion coefficients use micrometres, as glass catalogues do.
+#[derive(Copy, Clone, Debug)]
+pub enum Ior {
+    Constant(f64),
+    // n = a + b / lambda^2
+    Cauchy { a: f64, b: f64 },
+    // n^2 = 1 + sum of b_i lambda^2 / (lambda^2 - c_i)
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Ior {
+    // The sodium d line, where a glass's nominal index is quoted. RGB rendering uses the index here.
+    pub const REFERENCE_WAVELENGTH: f64 = 587.6;
+
+    pub fn at(&self, lambda: f64) -> f64 {
+        let l2 = (lambda / 1000.).powi(2);
+        match *self {
+            Self::Constant(n) => n,
+            Self::Cauchy { a, b } => a + b / l2,
+            Self::Sellmeier { b, c } => {
+                (1. + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>()).sqrt()
+            }
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, Self::Constant(_))
+    }
 }
 
 #[derive(Copy, Clone)]
 pub struct Dielectric {
-    pub ir: f64,
+    pub ior: Ior,
 }
 impl Dielectric {
     pub fn from(index_of_refraction: f64) -> Self {
         Self {
-            ir: index_of_refraction,
+            ior: Ior::Constant(index_of_refraction),
+        }
+    }
+
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        Self {
+            ior: Ior::Cauchy { a, b },
+        }
+    }
+
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Self {
+            ior: Ior::Sellmeier { b, c },
         }
     }
 
-    fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
+    // Schott N-BK7, a common crown glass with low dispersion.
+    pub fn bk7() -> Self {
+        Self::sellmeier(
+            [1.03961212, 0.231792344, 1.01046945],
+            [0.00600069867, 0.0200179144, 103.560653],
+        )
+    }
+
+    // Schott SF11, a dense flint glass with strong dispersion.
+    pub fn sf11() -> Self {
+        Self::sellmeier(
+            [1.73759695, 0.313747346, 1.89878101],
+            [0.013188707, 0.0623068142, 155.23629],
+        )
+    }
+
+    // Schlick's approximation of the Fresnel reflectance.
+    pub fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
         let r0 = (1. - ref_idx) / (1. + ref_idx);
         r0 * r0 + (1. - r0 * r0) * (1. - cosine).powi(5)
     }
@@ -97,26 +196,59 @@ impl Material for Dielectric {
         scattered: &mut Ray,
     ) -> bool {
         *attenuation = Colour::from(1., 1., 1.);
-        let refraction_ratio = if rec.front_face {
-            1. / self.ir
-        } else {
-            self.ir
+        let mut wavelengths = r_in.wavelengths;
+        let ir = match wavelengths {
+            Some(lambda) if self.ior.is_dispersive() => {
+                // Each wavelength would refract a different way, so only the hero wavelength
+                // carries on, weighted to stand in for the others.
+                let hero = lambda.x();
+                if lambda.y() != hero || lambda.z() != hero {
+                    *attenuation = Colour::from(3., 0., 0.);
+                    wavelengths = Some(Vec3::from(hero, hero, hero));
+                }
+                self.ior.at(hero)
+            }
+            _ => self.ior.at(Ior::REFERENCE_WAVELENGTH),
         };
+        let refraction_ratio = if rec.front_face { 1. / ir } else { ir };
         let unit_direction = r_in.direction().unit_vector();
 
         let cos_theta = dot(-unit_direction, rec.normal).min(1.);
         let sin_theta = (1. - cos_theta * cos_theta).sqrt();
 
         let cannot_refract = refraction_ratio * sin_theta > 1.;
-        let direction = if cannot_refract
+        *scattered = if cannot_refract
             || Self::reflectance(cos_theta, refraction_ratio) > random_f64(0., 1.)
         {
-            reflect(unit_direction, rec.normal)
+            Ray::from(rec.p, reflect(unit_direction, rec.normal)).with_kind(RayKind::Reflection)
         } else {
-            refract(unit_direction, rec.normal, refraction_ratio)
+            Ray::from(rec.p, refract(unit_direction, rec.normal, refraction_ratio))
+                .with_kind(RayKind::Refraction)
         };
-
-        *scattered = Ray::from(rec.p, direction);
+        scattered.wavelengths = wavelengths;
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glasses_match_their_catalogue_indices() {
+        // Schott's nominal indices at the d line.
+        let bk7 = Dielectric::bk7().ior.at(Ior::REFERENCE_WAVELENGTH);
+        assert!((bk7 - 1.5168).abs() < 1e-4, "BK7 index {}", bk7);
+        let sf11 = Dielectric::sf11().ior.at(Ior::REFERENCE_WAVELENGTH);
+        assert!((sf11 - 1.7847).abs() < 1e-4, "SF11 index {}", sf11);
+
+        // Both disperse normally, bending blue more than red.
+        for glass in [
+            Dielectric::bk7(),
+            Dielectric::sf11(),
+            Dielectric::cauchy(1.5, 0.004),
+        ] {
+            assert!(glass.ior.at(450.) > glass.ior.at(650.));
+        }
+    }
+}
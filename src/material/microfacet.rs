@@ -0,0 +1,177 @@
+use crate::{
+    material::material::Material,
+    objects::hittable::HitRecord,
+    ray::{Ray, RayKind},
+    spectrum::upsample,
+    utils::{degrees_to_radians, random_f64},
+    vec3::{cross, dot, Colour, Vec3},
+};
+use std::f64::consts::PI;
+
+// A rough metal with a GGX microfacet distribution that can be stretched along the surface, as
+// for brushed metal or satin. `alpha_x` is the roughness along the surface's u direction and
+// `alpha_y` across it, and `rotation` turns that direction about the normal, in degrees. The
+// albedo is the reflectance at normal incidence.
+#[derive(Copy, Clone)]
+pub struct AnisotropicMetal {
+    pub albedo: Colour,
+    pub alpha_x: f64,
+    pub alpha_y: f64,
+    pub rotation: f64,
+}
+
+impl AnisotropicMetal {
+    pub fn from(albedo: Colour, alpha_x: f64, alpha_y: f64, rotation: f64) -> Self {
+        Self {
+            albedo,
+            alpha_x: alpha_x.max(1e-4),
+            alpha_y: alpha_y.max(1e-4),
+            rotation,
+        }
+    }
+
+    // An orthonormal tangent, bitangent and normal at the hit, with the tangent following dpdu.
+    fn frame(&self, rec: &HitRecord) -> (Vec3, Vec3, Vec3) {
+        let n = rec.normal;
+        let mut t = rec.dpdu - n * dot(n, rec.dpdu);
+        if t.near_zero() {
+            t = if n.x().abs() > 0.9 {
+                cross(n, Vec3::from(0., 1., 0.))
+            } else {
+                cross(n, Vec3::from(1., 0., 0.))
+            };
+        }
+        let t = t.unit_vector();
+        let b = cross(n, t);
+
+        let angle = degrees_to_radians(self.rotation);
+        let (sin, cos) = angle.sin_cos();
+        (t * cos + b * sin, b * cos - t * sin, n)
+    }
+
+    // Smith's auxiliary function for a direction in the local frame.
+    fn lambda(&self, w: Vec3) -> f64 {
+        let z2 = w.z() * w.z();
+        if z2 == 0. {
+            return f64::INFINITY;
+        }
+        let a2 = (self.alpha_x * w.x()).powi(2) + (self.alpha_y * w.y()).powi(2);
+        (-1. + (1. + a2 / z2).sqrt()) / 2.
+    }
+
+    // Samples a microfacet normal from the distribution of normals visible from `wo`, following
+    // Heitz, "Sampling the GGX Distribution of Visible Normals" (2018).
+    fn sample_visible_normal(&self, wo: Vec3) -> Vec3 {
+        let vh = Vec3::from(self.alpha_x * wo.x(), self.alpha_y * wo.y(), wo.z()).unit_vector();
+
+        let length2 = vh.x() * vh.x() + vh.y() * vh.y();
+        let t1 = if length2 > 0. {
+            Vec3::from(-vh.y(), vh.x(), 0.) / length2.sqrt()
+        } else {
+            Vec3::from(1., 0., 0.)
+        };
+        let t2 = cross(vh, t1);
+
+        let r = random_f64(0., 1.).sqrt();
+        let phi = 2. * PI * random_f64(0., 1.);
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1. + vh.z());
+        let p2 = (1. - s) * (1. - p1 * p1).sqrt() + s * r * phi.sin();
+        let p3 = (1. - p1 * p1 - p2 * p2).max(0.).sqrt();
+
+        let nh = t1 * p1 + t2 * p2 + vh * p3;
+        Vec3::from(self.alpha_x * nh.x(), self.alpha_y * nh.y(), nh.z().max(0.)).unit_vector()
+    }
+}
+
+impl Material for AnisotropicMetal {
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: HitRecord,
+        attenuation: &mut Colour,
+        scattered: &mut Ray,
+    ) -> bool {
+        let (t, b, n) = self.frame(&rec);
+        let to_local = |v: Vec3| Vec3::from(dot(v, t), dot(v, b), dot(v, n));
+
+        let wo = to_local(-r_in.direction().unit_vector());
+        if wo.z() <= 0. {
+            return false;
+        }
+        let h = self.sample_visible_normal(wo);
+        let wi = h * (2. * dot(wo, h)) - wo;
+        if wi.z() <= 0. {
+            return false;
+        }
+
+        // With visible normal sampling the estimator reduces to the Fresnel term times the
+        // ratio of the masking-shadowing and masking functions.
+        let f0 = upsample(self.albedo, r_in.wavelengths);
+        let fresnel = f0 + (Colour::from(1., 1., 1.) - f0) * (1. - dot(wo, h)).powi(5);
+        let (lambda_o, lambda_i) = (self.lambda(wo), self.lambda(wi));
+        *attenuation = fresnel * ((1. + lambda_o) / (1. + lambda_o + lambda_i));
+
+        *scattered =
+            Ray::from(rec.p, t * wi.x() + b * wi.y() + n * wi.z()).with_kind(RayKind::Reflection);
+        true
+    }
+
+    fn albedo(&self, _rec: &HitRecord) -> Colour {
+        self.albedo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::{reflect, Point3};
+
+    fn hit() -> (Ray, HitRecord) {
+        let mut rec = HitRecord::new();
+        rec.normal = Vec3::from(0., 0., 1.);
+        rec.dpdu = Vec3::from(1., 0., 0.);
+        rec.front_face = true;
+        (
+            Ray::from(Point3::from(-1., 0.5, 1.), Vec3::from(1., -0.5, -1.)),
+            rec,
+        )
+    }
+
+    #[test]
+    fn smooth_metal_is_a_mirror() {
+        let (r_in, rec) = hit();
+        let mirrored = reflect(r_in.direction().unit_vector(), rec.normal);
+        let metal = AnisotropicMetal::from(Colour::from(0.9, 0.9, 0.9), 0., 0., 30.);
+        // GGX has long tails, so compare the average direction.
+        let samples = 1000;
+        let mut total = Vec3::new();
+        for _ in 0..samples {
+            let mut attenuation = Colour::new();
+            let mut scattered = Ray::from(Point3::new(), Vec3::new());
+            if metal.scatter(r_in, rec.clone(), &mut attenuation, &mut scattered) {
+                total += scattered.direction();
+            }
+        }
+        assert!((total / samples as f64 - mirrored).length() < 1e-3);
+    }
+
+    #[test]
+    fn isotropic_metal_reflects_above_the_surface() {
+        let (r_in, rec) = hit();
+        for rotation in [0., 45., 90.] {
+            let metal = AnisotropicMetal::from(Colour::from(0.9, 0.9, 0.9), 0.3, 0.3, rotation);
+            let mut reflected = 0;
+            for _ in 0..1000 {
+                let mut attenuation = Colour::new();
+                let mut scattered = Ray::from(Point3::new(), Vec3::new());
+                if metal.scatter(r_in, rec.clone(), &mut attenuation, &mut scattered) {
+                    reflected += 1;
+                    assert!(dot(scattered.direction(), rec.normal) > 0.);
+                    assert!(attenuation.x() > 0. && attenuation.x() <= 1.);
+                }
+            }
+            assert!(reflected > 800);
+        }
+    }
+}
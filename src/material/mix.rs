@@ -0,0 +1,131 @@
+use crate::{
+    material::material::Material,
+    medium::Medium,
+    objects::hittable::HitRecord,
+    ray::Ray,
+    utils::random_f64,
+    vec3::{Colour, Point3},
+};
+use std::rc::Rc;
+
+// Blends two materials, picking `b` with probability given by the weight at the hit point and
+// `a` otherwise.
+pub struct Mix {
+    pub a: Rc<dyn Material>,
+    pub b: Rc<dyn Material>,
+    pub weight: Rc<dyn Fn(Point3) -> f64>,
+}
+
+impl Mix {
+    pub fn from(
+        a: Rc<dyn Material>,
+        b: Rc<dyn Material>,
+        weight: Rc<dyn Fn(Point3) -> f64>,
+    ) -> Self {
+        Self { a, b, weight }
+    }
+
+    pub fn constant(a: Rc<dyn Material>, b: Rc<dyn Material>, weight: f64) -> Self {
+        Self::from(a, b, Rc::new(move |_| weight))
+    }
+
+    fn weight_at(&self, p: Point3) -> f64 {
+        (self.weight)(p).clamp(0., 1.)
+    }
+}
+
+impl Material for Mix {
+    fn accepts_hit(&self, rec: &HitRecord) -> bool {
+        if random_f64(0., 1.) < self.weight_at(rec.p) {
+            self.b.accepts_hit(rec)
+        } else {
+            self.a.accepts_hit(rec)
+        }
+    }
+
+    // Scattering picks one material later on, so the shading normal blends both materials'
+    // perturbations by the weight.
+    fn perturb(&self, r_in: Ray, rec: &mut HitRecord) {
+        let (mut rec_a, mut rec_b) = (rec.clone(), rec.clone());
+        self.a.perturb(r_in, &mut rec_a);
+        self.b.perturb(r_in, &mut rec_b);
+        let w = self.weight_at(rec.p);
+        let normal = rec_a.normal * (1. - w) + rec_b.normal * w;
+        if !normal.near_zero() {
+            rec.normal = normal.unit_vector();
+        }
+    }
+
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: HitRecord,
+        attenuation: &mut Colour,
+        scattered: &mut Ray,
+    ) -> bool {
+        if random_f64(0., 1.) < self.weight_at(rec.p) {
+            self.b.scatter(r_in, rec, attenuation, scattered)
+        } else {
+            self.a.scatter(r_in, rec, attenuation, scattered)
+        }
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Colour {
+        let w = self.weight_at(rec.p);
+        self.a.albedo(rec) * (1. - w) + self.b.albedo(rec) * w
+    }
+
+    fn diffuse(&self, rec: &HitRecord) -> Option<Colour> {
+        let w = self.weight_at(rec.p);
+        match (self.a.diffuse(rec), self.b.diffuse(rec)) {
+            (None, None) => None,
+            (a, b) => {
+                Some(a.unwrap_or_else(Colour::new) * (1. - w) + b.unwrap_or_else(Colour::new) * w)
+            }
+        }
+    }
+
+    fn medium(&self, rec: &HitRecord) -> Option<Medium> {
+        if random_f64(0., 1.) < self.weight_at(rec.p) {
+            self.b.medium(rec)
+        } else {
+            self.a.medium(rec)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::{Lambertian, Metal},
+        vec3::{reflect, Vec3},
+    };
+
+    #[test]
+    fn constant_weight_picks_one_material() {
+        let red = Colour::from(0.9, 0.1, 0.1);
+        let blue = Colour::from(0.1, 0.1, 0.9);
+        let diffuse: Rc<dyn Material> = Rc::new(Lambertian::from(red));
+        let mirror: Rc<dyn Material> = Rc::new(Metal::from(blue, 0.));
+
+        let mut rec = HitRecord::new();
+        rec.normal = Vec3::from(0., 0., 1.);
+        rec.front_face = true;
+        let r_in = Ray::from(Point3::from(-1., 0., 1.), Vec3::from(1., 0., -1.));
+        let mirrored = reflect(r_in.direction().unit_vector(), rec.normal);
+
+        for (weight, expected) in [(0., red), (1., blue)] {
+            let mix = Mix::constant(diffuse.clone(), mirror.clone(), weight);
+            assert!((mix.albedo(&rec) - expected).length() == 0.);
+            for _ in 0..100 {
+                let mut attenuation = Colour::new();
+                let mut scattered = Ray::from(Point3::new(), Vec3::new());
+                assert!(mix.scatter(r_in, rec.clone(), &mut attenuation, &mut scattered));
+                assert!((attenuation - expected).length() == 0.);
+                let is_mirrored = (scattered.direction() - mirrored).length() < 1e-12;
+                assert_eq!(is_mirrored, weight == 1.);
+            }
+        }
+    }
+}
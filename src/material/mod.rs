@@ -1 +1,10 @@
+pub mod bump;
+pub mod conductor;
+pub mod cutout;
+pub mod layered;
+#[allow(clippy::module_inception)]
 pub mod material;
+pub mod microfacet;
+pub mod mix;
+pub mod subsurface;
+pub mod thin_film;
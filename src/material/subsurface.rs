@@ -0,0 +1,45 @@
+use crate::{
+    material::material::{Dielectric, Material},
+    medium::Medium,
+    objects::hittable::HitRecord,
+    ray::Ray,
+    vec3::Colour,
+};
+
+// A translucent object such as skin, wax, marble or milk. Its surface is a smooth dielectric
+// boundary, and the path tracer follows a random walk through the medium inside, so it must be
+// used on closed objects.
+#[derive(Copy, Clone)]
+pub struct Subsurface {
+    pub medium: Medium,
+    pub boundary: Dielectric,
+}
+
+impl Subsurface {
+    pub fn from(albedo: Colour, mean_free_path: Colour, ior: f64) -> Self {
+        Self {
+            medium: Medium::from(albedo, mean_free_path),
+            boundary: Dielectric::from(ior),
+        }
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: HitRecord,
+        attenuation: &mut Colour,
+        scattered: &mut Ray,
+    ) -> bool {
+        self.boundary.scatter(r_in, rec, attenuation, scattered)
+    }
+
+    fn albedo(&self, _rec: &HitRecord) -> Colour {
+        self.medium.albedo
+    }
+
+    fn medium(&self, _rec: &HitRecord) -> Option<Medium> {
+        Some(self.medium)
+    }
+}
@@ -0,0 +1,272 @@
+use crate::{
+    material::material::Material,
+    medium::Medium,
+    objects::hittable::HitRecord,
+    ray::{Ray, RayKind},
+    spectrum::shading_wavelengths,
+    utils::random_f64,
+    vec3::{dot, reflect, Colour, Point3},
+};
+use std::{
+    f64::consts::PI,
+    ops::{Add, Div, Mul, Sub},
+    rc::Rc,
+};
+
+// A thin transparent coating over another material, such as a soap film, oil on a surface or the
+// oxide layer of anodised metal. Light reflected from the top and bottom of the film interferes,
+// so the coating reflects some wavelengths much more strongly than others depending on its
+// thickness and the viewing angle.
+//
+// Over a transparent or diffuse base the coating acts as a separate specular layer, and light it
+// doesn't reflect reaches the base. Over a metal, given by a complex base index, the film and the
+// metal reflect together: the base only picks the reflected direction and the stack's
+// reflectance replaces its colour.
+pub struct ThinFilm {
+    pub base: Rc<dyn Material>,
+    // Film thickness in nanometres at a point on the surface.
+    pub thickness: Rc<dyn Fn(Point3) -> f64>,
+    pub film_ior: f64,
+    // Complex index of refraction (n, k) under the film. Metals have k > 0.
+    pub base_ior: (f64, f64),
+}
+
+impl ThinFilm {
+    pub fn from(
+        base: Rc<dyn Material>,
+        thickness: Rc<dyn Fn(Point3) -> f64>,
+        film_ior: f64,
+        base_ior: (f64, f64),
+    ) -> Self {
+        Self {
+            base,
+            thickness,
+            film_ior,
+            base_ior,
+        }
+    }
+
+    pub fn uniform(
+        base: Rc<dyn Material>,
+        thickness: f64,
+        film_ior: f64,
+        base_ior: (f64, f64),
+    ) -> Self {
+        Self::from(base, Rc::new(move |_| thickness), film_ior, base_ior)
+    }
+
+    fn is_over_metal(&self) -> bool {
+        self.base_ior.1 > 0.
+    }
+
+    // Reflectance of the air, film and base stack at wavelength `lambda`, summing the multiple
+    // reflections within the film (the Airy formula), averaged over both polarisations.
+    fn reflectance(&self, cos_i: f64, thickness: f64, lambda: f64) -> f64 {
+        let n1 = 1.;
+        let n2 = self.film_ior;
+        let n3 = Complex::from(self.base_ior.0, self.base_ior.1);
+        let sin2_i = 1. - cos_i * cos_i;
+        let cos_film = (1. - sin2_i / (n2 * n2)).max(0.).sqrt();
+        let cos_base = (Complex::from(1., 0.) - Complex::from(sin2_i, 0.) / (n3 * n3)).sqrt();
+
+        let phase = 4. * PI * n2 * thickness * cos_film / lambda;
+        let shift = Complex::from(phase.cos(), phase.sin());
+        let airy = |r12: f64, r23: Complex| {
+            let r = (r23 * shift + r12) / (r23 * shift * r12 + 1.);
+            r.norm_squared()
+        };
+
+        let n2_cos_film = Complex::from(n2 * cos_film, 0.);
+        let rs = airy(
+            (n1 * cos_i - n2 * cos_film) / (n1 * cos_i + n2 * cos_film),
+            (n2_cos_film - n3 * cos_base) / (n2_cos_film + n3 * cos_base),
+        );
+        let rp = airy(
+            (n2 * cos_i - n1 * cos_film) / (n2 * cos_i + n1 * cos_film),
+            (n3 * cos_film - cos_base * n2) / (n3 * cos_film + cos_base * n2),
+        );
+        0.5 * (rs + rp)
+    }
+}
+
+impl Material for ThinFilm {
+    fn accepts_hit(&self, rec: &HitRecord) -> bool {
+        self.base.accepts_hit(rec)
+    }
+
+    fn perturb(&self, r_in: Ray, rec: &mut HitRecord) {
+        self.base.perturb(r_in, rec);
+    }
+
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: HitRecord,
+        attenuation: &mut Colour,
+        scattered: &mut Ray,
+    ) -> bool {
+        let unit_direction = r_in.direction().unit_vector();
+        let cos_i = dot(-unit_direction, rec.normal).clamp(0., 1.);
+        let thickness = (self.thickness)(rec.p).max(0.);
+        let lambda = shading_wavelengths(r_in.wavelengths);
+        let reflectance = Colour::from(
+            self.reflectance(cos_i, thickness, lambda.x()),
+            self.reflectance(cos_i, thickness, lambda.y()),
+            self.reflectance(cos_i, thickness, lambda.z()),
+        );
+
+        if self.is_over_metal() {
+            let mut base_attenuation = Colour::new();
+            if !self
+                .base
+                .scatter(r_in, rec, &mut base_attenuation, scattered)
+            {
+                return false;
+            }
+            *attenuation = reflectance;
+            return true;
+        }
+
+        // Choose between the coating and the base by the average reflectance, then weight the
+        // result so each wavelength gets its own share.
+        let p = (reflectance.x() + reflectance.y() + reflectance.z()) / 3.;
+        if random_f64(0., 1.) < p {
+            *scattered = Ray::from(rec.p, reflect(unit_direction, rec.normal))
+                .with_kind(RayKind::Reflection);
+            *attenuation = reflectance / p;
+            return true;
+        }
+
+        let mut base_attenuation = Colour::new();
+        if !self
+            .base
+            .scatter(r_in, rec, &mut base_attenuation, scattered)
+        {
+            return false;
+        }
+        *attenuation = base_attenuation * (Colour::from(1., 1., 1.) - reflectance) / (1. - p);
+        true
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Colour {
+        self.base.albedo(rec)
+    }
+
+    fn diffuse(&self, rec: &HitRecord) -> Option<Colour> {
+        self.base.diffuse(rec)
+    }
+
+    fn medium(&self, rec: &HitRecord) -> Option<Medium> {
+        self.base.medium(rec)
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn from(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    fn norm_squared(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    // Principal square root.
+    fn sqrt(self) -> Self {
+        let r = self.norm_squared().sqrt();
+        let re = ((r + self.re) / 2.).max(0.).sqrt();
+        let im = ((r - self.re) / 2.).max(0.).sqrt();
+        Self::from(re, if self.im < 0. { -im } else { im })
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::from(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Add<f64> for Complex {
+    type Output = Self;
+    fn add(self, other: f64) -> Self {
+        Self::from(self.re + other, self.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::from(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::from(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Mul<f64> for Complex {
+    type Output = Self;
+    fn mul(self, other: f64) -> Self {
+        Self::from(self.re * other, self.im * other)
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        let denominator = other.norm_squared();
+        Self::from(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::material::Lambertian;
+
+    fn film(film_ior: f64, base_ior: (f64, f64)) -> ThinFilm {
+        let base = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        ThinFilm::uniform(base, 0., film_ior, base_ior)
+    }
+
+    // Unpolarised Fresnel reflectance from air into a dielectric of index `n`.
+    fn fresnel(cos_i: f64, n: f64) -> f64 {
+        let cos_t = (1. - (1. - cos_i * cos_i) / (n * n)).sqrt();
+        let rs = (cos_i - n * cos_t) / (cos_i + n * cos_t);
+        let rp = (n * cos_i - cos_t) / (n * cos_i + cos_t);
+        0.5 * (rs * rs + rp * rp)
+    }
+
+    #[test]
+    fn vanishing_film_reflects_like_the_bare_base() {
+        for film_ior in [1.33, 2.4] {
+            let coated = film(film_ior, (1.5, 0.));
+            for cos_i in [1., 0.7, 0.3] {
+                for lambda in [450., 550., 650.] {
+                    let reflectance = coated.reflectance(cos_i, 0., lambda);
+                    assert!((reflectance - fresnel(cos_i, 1.5)).abs() < 1e-12);
+                }
+            }
+
+            let (n, k) = (0.2, 3.);
+            let metal = ((n - 1.) * (n - 1.) + k * k) / ((n + 1.) * (n + 1.) + k * k);
+            let reflectance = film(film_ior, (n, k)).reflectance(1., 0., 550.);
+            assert!((reflectance - metal).abs() < 1e-12);
+        }
+    }
+}
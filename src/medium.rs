@@ -0,0 +1,140 @@
+use crate::{
+    objects::hittable::{HitRecord, Hittable},
+    ray::{Ray, RayKind},
+    spectrum::upsample,
+    utils::{random_f64, INFINITY},
+    vec3::{dot, Colour, Vec3},
+};
+
+// Longest random walk followed before the path is given up as lost inside the medium.
+const MAX_STEPS: i32 = 256;
+
+// A homogeneous scattering medium filling a closed object. Each channel has its own mean free
+// path in world units, the average distance light travels before hitting a particle, while the
+// albedo is the chance of being scattered rather than absorbed at each particle.
+#[derive(Copy, Clone, Debug)]
+pub struct Medium {
+    pub albedo: Colour,
+    pub mean_free_path: Colour,
+}
+
+impl Medium {
+    pub fn from(albedo: Colour, mean_free_path: Colour) -> Self {
+        Self {
+            albedo,
+            mean_free_path,
+        }
+    }
+
+    // Follows a random walk from `r`, a ray travelling inside the medium whose next boundary hit
+    // is `rec`, scattering isotropically until the walk reaches the boundary. On return `r` and
+    // `rec` hold the final segment and its boundary hit, and the result is the path's weight.
+    // Returns `None` if the walk escapes through an open surface or doesn't finish.
+    //
+    // Distances are sampled using one randomly chosen channel and weighted by the density
+    // averaged over all channels, so each channel can have its own mean free path. Channels are
+    // chosen in proportion to their current weight, which keeps the weights of long walks
+    // through strongly coloured media from growing without bound.
+    pub fn walk(&self, world: &impl Hittable, r: &mut Ray, rec: &mut HitRecord) -> Option<Colour> {
+        let albedo = upsample(self.albedo, r.wavelengths);
+        let mean_free_path = upsample(self.mean_free_path, r.wavelengths);
+        let sigma_t = Colour::from(
+            1. / mean_free_path.x(),
+            1. / mean_free_path.y(),
+            1. / mean_free_path.z(),
+        );
+        let transmittance = |distance: f64| {
+            Colour::from(
+                (-sigma_t.x() * distance).exp(),
+                (-sigma_t.y() * distance).exp(),
+                (-sigma_t.z() * distance).exp(),
+            )
+        };
+
+        let mut weight = Colour::from(1., 1., 1.);
+        for _ in 0..MAX_STEPS {
+            let total = weight.x() + weight.y() + weight.z();
+            if total <= 0. {
+                return None;
+            }
+            let probabilities = weight / total;
+            let xi = random_f64(0., 1.);
+            let channel = if xi < probabilities.x() {
+                0
+            } else if xi < probabilities.x() + probabilities.y() {
+                1
+            } else {
+                2
+            };
+
+            let speed = r.direction().length();
+            let boundary = rec.t * speed;
+            let distance = -(1. - random_f64(0., 1.)).ln() / sigma_t[channel];
+
+            if distance >= boundary {
+                let tr = transmittance(boundary);
+                return Some(weight * tr / dot(probabilities, tr));
+            }
+
+            let tr = transmittance(distance);
+            weight = weight * albedo * sigma_t * tr / dot(probabilities, sigma_t * tr);
+
+            *r = Ray {
+                orig: r.at(distance / speed),
+                dir: Vec3::random_unit_vector(),
+                kind: RayKind::Diffuse,
+                wavelengths: r.wavelengths,
+            };
+            if !world.hit(*r, 0.001, INFINITY, rec) {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::material::Lambertian, objects::sphere::Sphere, vec3::Point3};
+    use std::rc::Rc;
+
+    // A walk from the centre of a unit sphere of the medium, with the boundary hit it starts from.
+    fn start() -> (Sphere, Ray, HitRecord) {
+        let m = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        let sphere = Sphere::from(Point3::new(), 1., m);
+        let r = Ray::from(Point3::new(), Vec3::from(1., 0., 0.));
+        let mut rec = HitRecord::new();
+        assert!(sphere.hit(r, 0.001, INFINITY, &mut rec));
+        (sphere, r, rec)
+    }
+
+    #[test]
+    fn clear_medium_lets_the_path_through() {
+        let medium = Medium::from(Colour::new(), Colour::from(1e12, 1e12, 1e12));
+        let (sphere, mut r, mut rec) = start();
+        let weight = medium.walk(&sphere, &mut r, &mut rec).unwrap();
+        assert!((weight - Colour::from(1., 1., 1.)).length() < 1e-9);
+        assert!(r.origin().length() == 0. && (rec.t - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn absorbing_walks_average_to_the_boundary_transmittance() {
+        // Nothing survives scattering, so only paths reaching the boundary count.
+        let mean_free_path = Colour::from(0.5, 1., 2.);
+        let medium = Medium::from(Colour::new(), mean_free_path);
+        let walks = 100_000;
+        let mut total = Colour::new();
+        for _ in 0..walks {
+            let (sphere, mut r, mut rec) = start();
+            if let Some(weight) = medium.walk(&sphere, &mut r, &mut rec) {
+                total += weight;
+            }
+        }
+        let average = total / walks as f64;
+        for channel in 0..3 {
+            let transmittance = (-1. / mean_free_path[channel]).exp();
+            assert!((average[channel] - transmittance).abs() < 0.02);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+// An axis-aligned bounding box.
+#[derive(Copy, Clone, Debug)]
+pub struct Aabb {
+    pub minimum: Point3,
+    pub maximum: Point3,
+}
+
+impl Aabb {
+    pub fn from(a: Point3, b: Point3) -> Self {
+        Self {
+            minimum: Point3::from(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            maximum: Point3::from(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    pub fn surrounding(a: Self, b: Self) -> Self {
+        Self::from(
+            Point3::from(
+                a.minimum.x().min(b.minimum.x()),
+                a.minimum.y().min(b.minimum.y()),
+                a.minimum.z().min(b.minimum.z()),
+            ),
+            Point3::from(
+                a.maximum.x().max(b.maximum.x()),
+                a.maximum.y().max(b.maximum.y()),
+                a.maximum.z().max(b.maximum.z()),
+            ),
+        )
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        (self.minimum + self.maximum) * 0.5
+    }
+
+    pub fn corners(&self) -> [Point3; 8] {
+        let (a, b) = (self.minimum, self.maximum);
+        [
+            Point3::from(a.x(), a.y(), a.z()),
+            Point3::from(b.x(), a.y(), a.z()),
+            Point3::from(a.x(), b.y(), a.z()),
+            Point3::from(b.x(), b.y(), a.z()),
+            Point3::from(a.x(), a.y(), b.z()),
+            Point3::from(b.x(), a.y(), b.z()),
+            Point3::from(a.x(), b.y(), b.z()),
+            Point3::from(b.x(), b.y(), b.z()),
+        ]
+    }
+
+    // Index of the axis along which the box is longest.
+    pub fn longest_axis(&self) -> usize {
+        let extent: Vec3 = self.maximum - self.minimum;
+        if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn hit(&self, r: Ray, t_min: f64, t_max: f64) -> bool {
+        self.interval(r, t_min, t_max).is_some()
+    }
+
+    // The part of [t_min, t_max] over which the ray is inside the box, if any.
+    pub fn interval(&self, r: Ray, mut t_min: f64, mut t_max: f64) -> Option<(f64, f64)> {
+        for axis in 0..3 {
+            let inv_d = 1. / r.direction()[axis];
+            let mut t0 = (self.minimum[axis] - r.origin()[axis]) * inv_d;
+            let mut t1 = (self.maximum[axis] - r.origin()[axis]) * inv_d;
+            if inv_d < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
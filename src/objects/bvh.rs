@@ -0,0 +1,217 @@
+use crate::{
+    objects::{
+        aabb::Aabb,
+        hittable::{hit_child, HitRecord, Hittable},
+        hittable_list::HittableList,
+    },
+    ray::Ray,
+};
+use std::rc::Rc;
+
+enum BvhNode {
+    Leaf {
+        object: Rc<dyn Hittable>,
+        first_id: usize,
+    },
+    Interior {
+        bbox: Aabb,
+        left: Box<BvhNode>,
+        right: Box<BvhNode>,
+    },
+}
+
+impl BvhNode {
+    // Builds a tree over objects paired with their bounding boxes and first IDs, splitting at
+    // the median centroid along the longest axis of the objects' extent.
+    fn build(mut objects: Vec<(Rc<dyn Hittable>, Aabb, usize)>) -> Self {
+        if objects.len() == 1 {
+            let (object, _, first_id) = objects.remove(0);
+            return Self::Leaf { object, first_id };
+        }
+
+        let bbox = objects
+            .iter()
+            .skip(1)
+            .fold(objects[0].1, |total, o| Aabb::surrounding(total, o.1));
+        let centroids = objects.iter().map(|o| o.1.centroid()).fold(
+            Aabb::from(objects[0].1.centroid(), objects[0].1.centroid()),
+            |total, c| Aabb::surrounding(total, Aabb::from(c, c)),
+        );
+        let axis = centroids.longest_axis();
+        objects.sort_by(|a, b| a.1.centroid()[axis].total_cmp(&b.1.centroid()[axis]));
+
+        let right = objects.split_off(objects.len() / 2);
+        Self::Interior {
+            bbox,
+            left: Box::new(Self::build(objects)),
+            right: Box::new(Self::build(right)),
+        }
+    }
+
+    fn hit(
+        &self,
+        r: Ray,
+        t_min: f64,
+        t_max: f64,
+        temp_rec: &mut HitRecord,
+        rec: &mut HitRecord,
+    ) -> bool {
+        match self {
+            Self::Leaf { object, first_id } => {
+                hit_child(object.as_ref(), *first_id, r, (t_min, t_max), temp_rec, rec)
+            }
+            Self::Interior { bbox, left, right } => {
+                if !bbox.hit(r, t_min, t_max) {
+                    return false;
+                }
+                let hit_left = left.hit(r, t_min, t_max, temp_rec, rec);
+                let closest = if hit_left { rec.t } else { t_max };
+                let hit_right = right.hit(r, t_min, closest, temp_rec, rec);
+                hit_left || hit_right
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        match self {
+            Self::Leaf { object, .. } => object
+                .bounding_box()
+                .expect("Bounding volume hierarchy leaves are bounded"),
+            Self::Interior { bbox, .. } => *bbox,
+        }
+    }
+}
+
+// A bounding volume hierarchy over the objects of a list, so rays only test the objects whose
+// boxes they pass through. Unbounded objects such as planes can't be placed in the tree and are
+// tested against every ray. Object IDs are the same as in the list.
+pub struct Bvh {
+    root: Option<BvhNode>,
+    unbounded: Vec<(Rc<dyn Hittable>, usize)>,
+    object_count: usize,
+}
+
+impl Bvh {
+    pub fn from(list: &HittableList) -> Self {
+        let mut bounded = vec![];
+        let mut unbounded = vec![];
+        let mut first_id = 0;
+        for object in &list.objects {
+            match object.bounding_box() {
+                Some(bbox) => bounded.push((object.clone(), bbox, first_id)),
+                None => unbounded.push((object.clone(), first_id)),
+            }
+            first_id += object.object_count();
+        }
+
+        Self {
+            root: if bounded.is_empty() {
+                None
+            } else {
+                Some(BvhNode::build(bounded))
+            },
+            unbounded,
+            object_count: first_id,
+        }
+    }
+}
+
+impl Hittable for Bvh {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let mut temp_rec = HitRecord::new();
+        let mut hit_anything = false;
+        let mut closest_so_far = t_max;
+
+        for (object, first_id) in &self.unbounded {
+            let range = (t_min, closest_so_far);
+            if hit_child(object.as_ref(), *first_id, r, range, &mut temp_rec, rec) {
+                hit_anything = true;
+                closest_so_far = rec.t;
+            }
+        }
+
+        if let Some(root) = &self.root {
+            if root.hit(r, t_min, closest_so_far, &mut temp_rec, rec) {
+                hit_anything = true;
+            }
+        }
+
+        hit_anything
+    }
+
+    fn object_count(&self) -> usize {
+        self.object_count
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if self.unbounded.is_empty() {
+            self.root.as_ref().map(BvhNode::bounding_box)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::Lambertian,
+        objects::{plane::Plane, sphere::Sphere},
+        utils::random_f64,
+        vec3::{Colour, Point3, Vec3},
+    };
+
+    // A floor under a field of random spheres, some grouped in a nested list.
+    fn world() -> HittableList {
+        let m = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        let mut world = HittableList::new();
+        world.add(Rc::new(Plane::from(
+            Point3::new(),
+            Vec3::from(0., 1., 0.),
+            m.clone(),
+        )));
+        let mut group = HittableList::new();
+        for i in 0..200 {
+            let centre = Point3::from(
+                random_f64(-10., 10.),
+                random_f64(0., 4.),
+                random_f64(-10., 10.),
+            );
+            let sphere = Rc::new(Sphere::from(centre, random_f64(0.1, 1.), m.clone()));
+            if i % 10 == 0 {
+                group.add(sphere);
+            } else {
+                world.add(sphere);
+            }
+        }
+        world.add(Rc::new(group));
+        world
+    }
+
+    #[test]
+    fn finds_the_same_hits_as_the_list() {
+        let list = world();
+        let bvh = Bvh::from(&list);
+        assert_eq!(bvh.object_count(), list.object_count());
+
+        let mut hits = 0;
+        for _ in 0..10_000 {
+            let origin = Point3::from(
+                random_f64(-12., 12.),
+                random_f64(0.1, 6.),
+                random_f64(-12., 12.),
+            );
+            let r = Ray::from(origin, Vec3::random_unit_vector());
+            let (mut expected, mut actual) = (HitRecord::new(), HitRecord::new());
+            let hit = list.hit(r, 0.001, f64::INFINITY, &mut expected);
+            assert_eq!(bvh.hit(r, 0.001, f64::INFINITY, &mut actual), hit);
+            if hit {
+                hits += 1;
+                assert_eq!(actual.t, expected.t);
+                assert_eq!(actual.object_id, expected.object_id);
+            }
+        }
+        assert!(hits > 1000);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    objects::{
+        aabb::Aabb,
+        hittable::{HitRecord, Hittable, Span},
+    },
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+use std::rc::Rc;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    // The first object with the second taken away.
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(&self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            Self::Union => in_a || in_b,
+            Self::Intersection => in_a && in_b,
+            Self::Difference => in_a && !in_b,
+        }
+    }
+}
+
+// Combines two closed objects, which must report their spans, into one solid. The surface of the
+// result is made up of pieces of each object's surface, which keep their own material, and where
+// the second object bites into the first its normals are turned to face out of the result.
+pub struct Csg {
+    pub op: CsgOp,
+    pub a: Rc<dyn Hittable>,
+    pub b: Rc<dyn Hittable>,
+}
+
+impl Csg {
+    pub fn from(op: CsgOp, a: Rc<dyn Hittable>, b: Rc<dyn Hittable>) -> Result<Self, String> {
+        let probe = Ray::from(Point3::new(), Vec3::from(1., 0., 0.));
+        if a.spans(probe).is_none() || b.spans(probe).is_none() {
+            return Err("CSG needs closed objects that report their spans".to_string());
+        }
+        Ok(Self { op, a, b })
+    }
+
+    pub fn union(a: Rc<dyn Hittable>, b: Rc<dyn Hittable>) -> Result<Self, String> {
+        Self::from(CsgOp::Union, a, b)
+    }
+
+    pub fn intersection(a: Rc<dyn Hittable>, b: Rc<dyn Hittable>) -> Result<Self, String> {
+        Self::from(CsgOp::Intersection, a, b)
+    }
+
+    pub fn difference(a: Rc<dyn Hittable>, b: Rc<dyn Hittable>) -> Result<Self, String> {
+        Self::from(CsgOp::Difference, a, b)
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let spans = self.spans(r).unwrap_or_default();
+        for surface in spans.iter().flat_map(|span| [&span.enter, &span.exit]) {
+            if surface.t < t_min || t_max < surface.t {
+                continue;
+            }
+            if surface.mat_ptr.accepts_hit(surface) {
+                *rec = surface.clone();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let (a, b) = (self.a.bounding_box(), self.b.bounding_box());
+        match self.op {
+            CsgOp::Union => Some(Aabb::surrounding(a?, b?)),
+            CsgOp::Intersection => match (a, b) {
+                (Some(a), Some(b)) => Some(Aabb::from(
+                    Point3::from(
+                        a.minimum.x().max(b.minimum.x()),
+                        a.minimum.y().max(b.minimum.y()),
+                        a.minimum.z().max(b.minimum.z()),
+                    ),
+                    Point3::from(
+                        a.maximum.x().min(b.maximum.x()),
+                        a.maximum.y().min(b.maximum.y()),
+                        a.maximum.z().min(b.maximum.z()),
+                    ),
+                )),
+                (a, b) => a.or(b),
+            },
+            CsgOp::Difference => a,
+        }
+    }
+
+    // Sweeps along the ray through the surfaces of both objects in order, tracking whether it is
+    // inside each, and keeps the surfaces where that changes whether it is inside the result.
+    fn spans(&self, r: Ray) -> Option<Vec<Span>> {
+        let spans_a = self.a.spans(r)?;
+        let spans_b = self.b.spans(r)?;
+
+        // Each surface, whether it belongs to the second object and whether the ray enters it.
+        let mut surfaces: Vec<(HitRecord, bool, bool)> = vec![];
+        for (spans, from_b) in [(spans_a, false), (spans_b, true)] {
+            for span in spans {
+                surfaces.push((span.enter, from_b, true));
+                surfaces.push((span.exit, from_b, false));
+            }
+        }
+        surfaces.sort_by(|x, y| x.0.t.total_cmp(&y.0.t));
+
+        let (mut in_a, mut in_b) = (false, false);
+        let mut inside = false;
+        let mut enter: Option<HitRecord> = None;
+        let mut spans = vec![];
+        for (mut surface, from_b, entering) in surfaces {
+            if from_b {
+                in_b = entering;
+            } else {
+                in_a = entering;
+            }
+            if self.op.inside(in_a, in_b) == inside {
+                continue;
+            }
+            inside = !inside;
+
+            if from_b && self.op == CsgOp::Difference && surface.t.is_finite() {
+                let outward_normal = if surface.front_face {
+                    surface.normal
+                } else {
+                    -surface.normal
+                };
+                surface.set_face_normal(r, -outward_normal);
+            }
+            if inside {
+                enter = Some(surface);
+            } else if let Some(enter) = enter.take() {
+                spans.push(Span {
+                    enter,
+                    exit: surface,
+                });
+            }
+        }
+        Some(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::Lambertian,
+        objects::{plane::Plane, quadric::Cylinder, sphere::Sphere},
+        vec3::Colour,
+    };
+
+    fn sphere(centre: Point3) -> Rc<dyn Hittable> {
+        let m = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        Rc::new(Sphere::from(centre, 1., m))
+    }
+
+    fn floor() -> Rc<dyn Hittable> {
+        let m = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        Rc::new(Plane::from(Point3::new(), Vec3::from(0., 1., 0.), m))
+    }
+
+    fn hit(object: &Csg, r: Ray) -> Option<HitRecord> {
+        let mut rec = HitRecord::new();
+        if object.hit(r, 0.001, f64::INFINITY, &mut rec) {
+            Some(rec)
+        } else {
+            None
+        }
+    }
+
+    // A unit sphere at the origin with a bite taken out of it by a unit sphere at x = 1.
+    fn bitten() -> Csg {
+        Csg::difference(sphere(Point3::new()), sphere(Point3::from(1., 0., 0.))).unwrap()
+    }
+
+    #[test]
+    fn difference_spans_end_at_the_bite() {
+        let r = Ray::from(Point3::from(-5., 0., 0.), Vec3::from(1., 0., 0.));
+        let spans = bitten().spans(r).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].enter.t - 4.).abs() < 1e-12);
+        assert!((spans[0].exit.t - 5.).abs() < 1e-12);
+        assert!(spans[0].enter.front_face && !spans[0].exit.front_face);
+    }
+
+    #[test]
+    fn flips_normals_on_the_bite_surface() {
+        // Coming through the bite, the ray first meets the sphere taken away, from inside it.
+        let r = Ray::from(Point3::from(5., 0., 0.), Vec3::from(-1., 0., 0.));
+        let rec = hit(&bitten(), r).unwrap();
+        assert!((rec.t - 5.).abs() < 1e-12);
+        assert!(rec.front_face);
+        assert!((rec.normal - Vec3::from(1., 0., 0.)).length() < 1e-12);
+        assert!((rec.geometric_normal - rec.normal).length() < 1e-12);
+    }
+
+    #[test]
+    fn intersection_of_disjoint_objects_is_empty() {
+        let apart =
+            Csg::intersection(sphere(Point3::new()), sphere(Point3::from(3., 0., 0.))).unwrap();
+        let r = Ray::from(Point3::from(-5., 0., 0.), Vec3::from(1., 0., 0.));
+        assert!(apart.spans(r).unwrap().is_empty());
+        assert!(hit(&apart, r).is_none());
+    }
+
+    #[test]
+    fn planes_cut_objects_in_half() {
+        // The plane encloses everything below it, leaving the lower half of the sphere.
+        let lower = Csg::intersection(sphere(Point3::new()), floor()).unwrap();
+        let down = Ray::from(Point3::from(0., 5., 0.), Vec3::from(0., -1., 0.));
+        let rec = hit(&lower, down).unwrap();
+        assert!((rec.t - 5.).abs() < 1e-12);
+        assert!((rec.normal - Vec3::from(0., 1., 0.)).length() < 1e-12);
+
+        let spans = lower.spans(down).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].exit.t - 6.).abs() < 1e-12);
+
+        // Taking the plane away leaves the upper half, whose flat face looks down.
+        let upper = Csg::difference(sphere(Point3::new()), floor()).unwrap();
+        let up = Ray::from(Point3::from(0., -5., 0.), Vec3::from(0., 1., 0.));
+        let rec = hit(&upper, up).unwrap();
+        assert!((rec.t - 5.).abs() < 1e-12);
+        assert!((rec.normal - Vec3::from(0., -1., 0.)).length() < 1e-12);
+    }
+
+    #[test]
+    fn rejects_open_objects() {
+        let m = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        let tube = Rc::new(Cylinder::from(Point3::new(), 1., (0., 1.), 360., false, m));
+        assert!(Csg::union(sphere(Point3::new()), tube).is_err());
+    }
+}
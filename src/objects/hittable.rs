@@ -1,5 +1,6 @@
 use crate::{
     material::material::{Lambertian, Material},
+    objects::{aabb::Aabb, visibility::Visibility},
     ray::Ray,
     vec3::{dot, Colour, Point3, Vec3},
 };
@@ -8,10 +9,22 @@ use std::rc::Rc;
 #[derive(Clone)]
 pub struct HitRecord {
     pub p: Point3,
+    // The normal used for shading, facing against the ray. Materials may perturb it, while
+    // `geometric_normal` stays the true normal of the surface, facing the same side.
     pub normal: Vec3,
+    pub geometric_normal: Vec3,
     pub mat_ptr: Rc<dyn Material>,
     pub t: f64,
     pub front_face: bool,
+    // Surface coordinates of the hit and the derivatives of the position along them, which give
+    // the surface's tangent frame.
+    pub u: f64,
+    pub v: f64,
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
+    pub object_id: usize,
+    // Bit `i` is set when the hit object is lit by light `i`.
+    pub light_mask: u64,
 }
 
 impl HitRecord {
@@ -19,9 +32,24 @@ impl HitRecord {
         Self {
             p: Point3::new(),
             normal: Vec3::new(),
+            geometric_normal: Vec3::new(),
             mat_ptr: Rc::new(Lambertian::from(Colour::new())),
             t: 0.,
             front_face: true,
+            u: 0.,
+            v: 0.,
+            dpdu: Vec3::new(),
+            dpdv: Vec3::new(),
+            object_id: 0,
+            light_mask: u64::MAX,
+        }
+    }
+
+    // The end of a span reaching to infinity in the direction of `t`, which no ray ever hits.
+    pub fn at_infinity(t: f64) -> Self {
+        Self {
+            t: f64::INFINITY.copysign(t),
+            ..Self::new()
         }
     }
 
@@ -32,9 +60,86 @@ impl HitRecord {
         } else {
             -outward_normal
         };
+        self.geometric_normal = self.normal;
     }
 }
 
+// A stretch of a ray inside a closed object, with the surfaces where it enters and leaves.
+#[derive(Clone)]
+pub struct Span {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
+}
+
+// Pairs up crossings of a closed surface, which alternate between entering and leaving, into
+// spans. A ray grazing the surface can lose one of a pair of close crossings, so an odd one out
+// is dropped.
+pub fn spans_from_crossings(
+    crossings: &[f64],
+    set_record: impl Fn(f64, &mut HitRecord),
+) -> Vec<Span> {
+    crossings
+        .chunks_exact(2)
+        .map(|pair| {
+            let mut span = Span {
+                enter: HitRecord::new(),
+                exit: HitRecord::new(),
+            };
+            set_record(pair[0], &mut span.enter);
+            set_record(pair[1], &mut span.exit);
+            span
+        })
+        .collect()
+}
+
 pub trait Hittable {
     fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool;
+
+    // Number of objects given their own ID. Containers set `HitRecord::object_id` to the index of
+    // the hit object among all the objects they contain.
+    fn object_count(&self) -> usize {
+        1
+    }
+
+    // Kinds of ray that can see the object. Containers skip objects the current ray can't see.
+    fn visibility(&self) -> Visibility {
+        Visibility::all()
+    }
+
+    // A box containing the object, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+
+    // Every stretch of the line through `r` that lies inside the object, in order, or `None` if
+    // the object doesn't enclose a volume. Unlike hits, spans aren't limited to a range of t, so
+    // they can start behind the ray's origin or reach to infinity.
+    fn spans(&self, _r: Ray) -> Option<Vec<Span>> {
+        None
+    }
+}
+
+// Intersects one object of a container whose objects' IDs start at `first_id`, skipping it if
+// the ray can't see it. `temp_rec` is scratch space, and `rec` is only changed if the object is
+// hit.
+pub fn hit_child(
+    object: &dyn Hittable,
+    first_id: usize,
+    r: Ray,
+    (t_min, t_max): (f64, f64),
+    temp_rec: &mut HitRecord,
+    rec: &mut HitRecord,
+) -> bool {
+    if !object.visibility().visible_to(r.kind) {
+        return false;
+    }
+
+    temp_rec.object_id = 0;
+    temp_rec.light_mask = u64::MAX;
+    if !object.hit(r, t_min, t_max, temp_rec) {
+        return false;
+    }
+    temp_rec.object_id += first_id;
+    *rec = temp_rec.clone();
+    true
 }
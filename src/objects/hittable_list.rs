@@ -1,23 +1,33 @@
 use crate::{
-    objects::hittable::{HitRecord, Hittable},
+    objects::{
+        aabb::Aabb,
+        hittable::{hit_child, HitRecord, Hittable},
+    },
     ray::Ray,
 };
 use std::rc::Rc;
 
 pub struct HittableList {
     pub objects: Vec<Rc<dyn Hittable>>,
+    object_count: usize,
 }
 
 impl HittableList {
     pub fn new() -> Self {
-        Self { objects: vec![] }
+        Self {
+            objects: vec![],
+            object_count: 0,
+        }
     }
 
     pub fn add(&mut self, object: Rc<dyn Hittable>) {
+        self.object_count += object.object_count();
         self.objects.push(object);
     }
+    #[allow(dead_code)]
     pub fn clear(&mut self) {
-        self.objects.clear()
+        self.objects.clear();
+        self.object_count = 0;
     }
 }
 
@@ -27,14 +37,27 @@ impl Hittable for HittableList {
         let mut hit_anything = false;
         let mut closest_so_far = t_max;
 
+        let mut first_id = 0;
+
         for object in &self.objects {
-            if object.hit(r, t_min, closest_so_far, &mut temp_rec) {
+            let range = (t_min, closest_so_far);
+            if hit_child(object.as_ref(), first_id, r, range, &mut temp_rec, rec) {
                 hit_anything = true;
-                closest_so_far = temp_rec.clone().t;
-                *rec = temp_rec.clone();
+                closest_so_far = rec.t;
             }
+            first_id += object.object_count();
         }
 
         hit_anything
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut boxes = self.objects.iter().map(|object| object.bounding_box());
+        let first = boxes.next()??;
+        boxes.try_fold(first, |total, b| Some(Aabb::surrounding(total, b?)))
+    }
+
+    fn object_count(&self) -> usize {
+        self.object_count
+    }
 }
@@ -1,3 +1,12 @@
+pub mod aabb;
+pub mod bvh;
+pub mod csg;
 pub mod hittable;
 pub mod hittable_list;
+pub mod plane;
+pub mod quadric;
+pub mod sdf;
 pub mod sphere;
+pub mod torus;
+pub mod transform;
+pub mod visibility;
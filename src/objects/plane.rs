@@ -0,0 +1,107 @@
+use crate::{
+    material::material::Material,
+    objects::{
+        aabb::Aabb,
+        hittable::{HitRecord, Hittable, Span},
+    },
+    ray::Ray,
+    vec3::{cross, dot, Point3, Vec3},
+};
+use std::rc::Rc;
+
+// An infinite plane through `point`. Its surface coordinates are distances in world units along
+// two directions in the plane, so textures tile across it. Having no bounds, it is kept out of
+// bounding volume hierarchies. For constructive solid geometry it encloses the half-space behind
+// its normal.
+pub struct Plane {
+    pub point: Point3,
+    pub normal: Vec3,
+    pub mat_ptr: Rc<dyn Material>,
+    tangent: Vec3,
+    bitangent: Vec3,
+}
+
+impl Plane {
+    pub fn from(point: Point3, normal: Vec3, m: Rc<dyn Material>) -> Self {
+        let normal = normal.unit_vector();
+        // Follow the x axis where possible, so a ground plane's u runs along x.
+        let helper = if normal.z().abs() < 0.9 {
+            Vec3::from(0., 0., 1.)
+        } else {
+            Vec3::from(0., 1., 0.)
+        };
+        let tangent = cross(normal, helper).unit_vector();
+        Self {
+            point,
+            normal,
+            mat_ptr: m,
+            tangent,
+            bitangent: cross(normal, tangent),
+        }
+    }
+
+    fn set_record(&self, r: Ray, t: f64, rec: &mut HitRecord) {
+        rec.t = t;
+        rec.p = r.at(rec.t);
+        rec.set_face_normal(r, self.normal);
+        let offset = rec.p - self.point;
+        rec.u = dot(offset, self.tangent);
+        rec.v = dot(offset, self.bitangent);
+        rec.dpdu = self.tangent;
+        rec.dpdv = self.bitangent;
+        rec.mat_ptr = self.mat_ptr.clone();
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let denominator = dot(self.normal, r.direction());
+        if denominator.abs() < 1e-12 {
+            return false;
+        }
+        let root = dot(self.point - r.origin(), self.normal) / denominator;
+        if root < t_min || t_max < root {
+            return false;
+        }
+
+        self.set_record(r, root, rec);
+        self.mat_ptr.accepts_hit(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+
+    fn spans(&self, r: Ray) -> Option<Vec<Span>> {
+        let denominator = dot(self.normal, r.direction());
+        if denominator.abs() < 1e-12 {
+            // Parallel rays are either inside throughout or never.
+            return Some(if dot(r.origin() - self.point, self.normal) < 0. {
+                vec![Span {
+                    enter: HitRecord::at_infinity(-1.),
+                    exit: HitRecord::at_infinity(1.),
+                }]
+            } else {
+                vec![]
+            });
+        }
+
+        let mut surface = HitRecord::new();
+        self.set_record(
+            r,
+            dot(self.point - r.origin(), self.normal) / denominator,
+            &mut surface,
+        );
+        Some(vec![if denominator < 0. {
+            Span {
+                enter: surface,
+                exit: HitRecord::at_infinity(1.),
+            }
+        } else {
+            Span {
+                enter: HitRecord::at_infinity(-1.),
+                exit: surface,
+            }
+        }])
+    }
+}
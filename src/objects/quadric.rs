@@ -0,0 +1,699 @@
+use crate::{
+    material::material::Material,
+    objects::{
+        aabb::Aabb,
+        hittable::{HitRecord, Hittable, Span},
+    },
+    ray::Ray,
+    utils::degrees_to_radians,
+    vec3::{Point3, Vec3},
+};
+use std::{f64::consts::PI, rc::Rc};
+
+// Surfaces of revolution about a vertical axis through `centre`, written as
+// x^2 + z^2 = f(y) in coordinates relative to the centre. Each is cut to a range of heights and
+// swept through `phi_max` degrees, starting from +x and turning towards +z. Surface coordinates
+// are u = phi / phi_max and v running from the bottom of the height range to the top.
+trait Quadric {
+    fn centre(&self) -> Point3;
+    fn mat_ptr(&self) -> Rc<dyn Material>;
+    fn y_range(&self) -> (f64, f64);
+    // Sweep angle in radians.
+    fn phi_max(&self) -> f64;
+    // Coefficients of the quadratic in t for a ray with local origin `o` and direction `d`.
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64);
+    // f'(y), giving the surface's slope and normal.
+    fn f_prime(&self, y: f64) -> f64;
+    // Largest radius over the height range.
+    fn max_radius(&self) -> f64;
+
+    // Angle of a local point around the axis, in [0, 2pi).
+    fn phi(&self, p: Vec3) -> f64 {
+        let phi = p.z().atan2(p.x());
+        if phi < 0. {
+            phi + 2. * PI
+        } else {
+            phi
+        }
+    }
+
+    // Fills in a hit on the curved surface at `t`.
+    fn set_surface_record(&self, r: Ray, t: f64, rec: &mut HitRecord) {
+        let (y_min, y_max) = self.y_range();
+        let p = r.at(t) - self.centre();
+        let f_prime = self.f_prime(p.y());
+        let gradient = Vec3::from(2. * p.x(), -f_prime, 2. * p.z());
+        // The surface has no normal at a singular point such as a cone's apex, so the axis stands
+        // in for it.
+        let outward_normal = if gradient.near_zero() {
+            Vec3::from(0., 1., 0.)
+        } else {
+            gradient.unit_vector()
+        };
+        let radius = (p.x() * p.x() + p.z() * p.z()).sqrt();
+
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.set_face_normal(r, outward_normal);
+        rec.u = self.phi(p) / self.phi_max();
+        rec.v = (p.y() - y_min) / (y_max - y_min);
+        rec.dpdu = Vec3::from(-p.z(), 0., p.x()) * self.phi_max();
+        rec.dpdv = if radius > 1e-12 {
+            let dr_dy = f_prime / (2. * radius);
+            Vec3::from(p.x() / radius * dr_dy, 1., p.z() / radius * dr_dy) * (y_max - y_min)
+        } else {
+            Vec3::new()
+        };
+        rec.mat_ptr = self.mat_ptr();
+    }
+
+    // Nearest hit on the curved surface.
+    fn hit_surface(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let o = r.origin() - self.centre();
+        let d = r.direction();
+        let (a, b, c) = self.coefficients(o, d);
+        let (y_min, y_max) = self.y_range();
+
+        for t in solve_quadratic(a, b, c) {
+            if t < t_min || t_max < t {
+                continue;
+            }
+            let p = o + d * t;
+            let phi = self.phi(p);
+            if p.y() < y_min || p.y() > y_max || phi > self.phi_max() {
+                continue;
+            }
+
+            self.set_surface_record(r, t, rec);
+            if rec.mat_ptr.accepts_hit(rec) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn quadric_bounding_box(&self) -> Aabb {
+        let (y_min, y_max) = self.y_range();
+        let r = self.max_radius();
+        Aabb::from(
+            self.centre() + Vec3::from(-r, y_min, -r),
+            self.centre() + Vec3::from(r, y_max, r),
+        )
+    }
+}
+
+// Real roots of a t^2 + b t + c = 0 in increasing order, computed so as to avoid cancellation.
+fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return if b.abs() < 1e-12 {
+            vec![]
+        } else {
+            vec![-c / b]
+        };
+    }
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return vec![];
+    }
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (t0, t1) = if q == 0. { (0., 0.) } else { (q / a, c / q) };
+    if t0 < t1 {
+        vec![t0, t1]
+    } else {
+        vec![t1, t0]
+    }
+}
+
+// A cylinder of `radius` standing on `centre`, from `y_min` to `y_max` above it. Capped
+// cylinders are closed with discs (or sectors, when partly swept) at both ends.
+pub struct Cylinder {
+    pub centre: Point3,
+    pub radius: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+    pub phi_max: f64,
+    pub capped: bool,
+    pub mat_ptr: Rc<dyn Material>,
+}
+
+impl Cylinder {
+    pub fn from(
+        centre: Point3,
+        radius: f64,
+        (y_min, y_max): (f64, f64),
+        phi_max: f64,
+        capped: bool,
+        m: Rc<dyn Material>,
+    ) -> Self {
+        Self {
+            centre,
+            radius,
+            y_min: y_min.min(y_max),
+            y_max: y_min.max(y_max),
+            phi_max: degrees_to_radians(phi_max.clamp(0., 360.)),
+            capped,
+            mat_ptr: m,
+        }
+    }
+
+    // Fills in a hit on the cap at height `y` at `t`.
+    fn set_cap_record(&self, r: Ray, y: f64, t: f64, rec: &mut HitRecord) {
+        let p = r.at(t) - self.centre;
+        let radius = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let outward_normal = if y == self.y_max {
+            Vec3::from(0., 1., 0.)
+        } else {
+            Vec3::from(0., -1., 0.)
+        };
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.set_face_normal(r, outward_normal);
+        rec.u = self.phi(p) / self.phi_max;
+        rec.v = radius / self.radius;
+        rec.dpdu = Vec3::from(-p.z(), 0., p.x()) * self.phi_max;
+        rec.dpdv = if radius > 1e-12 {
+            Vec3::from(p.x(), 0., p.z()) * (self.radius / radius)
+        } else {
+            Vec3::new()
+        };
+        rec.mat_ptr = self.mat_ptr.clone();
+    }
+
+    fn hit_cap(&self, r: Ray, y: f64, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let o = r.origin() - self.centre;
+        let d = r.direction();
+        if d.y().abs() < 1e-12 {
+            return false;
+        }
+        let t = (y - o.y()) / d.y();
+        if t < t_min || t_max < t {
+            return false;
+        }
+        let p = o + d * t;
+        let radius = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let phi = self.phi(p);
+        if radius > self.radius || phi > self.phi_max {
+            return false;
+        }
+
+        self.set_cap_record(r, y, t, rec);
+        rec.mat_ptr.accepts_hit(rec)
+    }
+}
+
+impl Quadric for Cylinder {
+    fn centre(&self) -> Point3 {
+        self.centre
+    }
+    fn mat_ptr(&self) -> Rc<dyn Material> {
+        self.mat_ptr.clone()
+    }
+    fn y_range(&self) -> (f64, f64) {
+        (self.y_min, self.y_max)
+    }
+    fn phi_max(&self) -> f64 {
+        self.phi_max
+    }
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64) {
+        (
+            d.x() * d.x() + d.z() * d.z(),
+            2. * (o.x() * d.x() + o.z() * d.z()),
+            o.x() * o.x() + o.z() * o.z() - self.radius * self.radius,
+        )
+    }
+    fn f_prime(&self, _y: f64) -> f64 {
+        0.
+    }
+    fn max_radius(&self) -> f64 {
+        self.radius
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let mut temp_rec = HitRecord::new();
+        let mut closest = t_max;
+        let mut hit_anything = false;
+        if self.hit_surface(r, t_min, closest, &mut temp_rec) {
+            hit_anything = true;
+            closest = temp_rec.t;
+            *rec = temp_rec.clone();
+        }
+        if self.capped {
+            for y in [self.y_min, self.y_max] {
+                if self.hit_cap(r, y, t_min, closest, &mut temp_rec) {
+                    hit_anything = true;
+                    closest = temp_rec.t;
+                    *rec = temp_rec.clone();
+                }
+            }
+        }
+        hit_anything
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.quadric_bounding_box())
+    }
+
+    // Only a capped cylinder swept all the way round is closed. The ray is inside it where it is
+    // both inside the infinite tube and between the planes of the caps.
+    fn spans(&self, r: Ray) -> Option<Vec<Span>> {
+        if !self.capped || self.phi_max < 2. * PI - 1e-9 {
+            return None;
+        }
+        let o = r.origin() - self.centre;
+        let d = r.direction();
+
+        let (a, b, c) = self.coefficients(o, d);
+        let tube = if a < 1e-12 {
+            if c >= 0. {
+                return Some(vec![]);
+            }
+            (f64::NEG_INFINITY, f64::INFINITY)
+        } else {
+            match solve_quadratic(a, b, c)[..] {
+                [t0, t1] if t0 < t1 => (t0, t1),
+                _ => return Some(vec![]),
+            }
+        };
+
+        // Heights in the order the ray crosses them.
+        let (slab, heights) = if d.y().abs() < 1e-12 {
+            if o.y() < self.y_min || o.y() > self.y_max {
+                return Some(vec![]);
+            }
+            ((f64::NEG_INFINITY, f64::INFINITY), (self.y_min, self.y_max))
+        } else {
+            let heights = if d.y() > 0. {
+                (self.y_min, self.y_max)
+            } else {
+                (self.y_max, self.y_min)
+            };
+            let t = |y: f64| (y - o.y()) / d.y();
+            ((t(heights.0), t(heights.1)), heights)
+        };
+
+        let (enter, exit) = (tube.0.max(slab.0), tube.1.min(slab.1));
+        if enter >= exit {
+            return Some(vec![]);
+        }
+        let mut span = Span {
+            enter: HitRecord::new(),
+            exit: HitRecord::new(),
+        };
+        if tube.0 >= slab.0 {
+            self.set_surface_record(r, enter, &mut span.enter);
+        } else {
+            self.set_cap_record(r, heights.0, enter, &mut span.enter);
+        }
+        if tube.1 <= slab.1 {
+            self.set_surface_record(r, exit, &mut span.exit);
+        } else {
+            self.set_cap_record(r, heights.1, exit, &mut span.exit);
+        }
+        Some(vec![span])
+    }
+}
+
+// A cone with its base of `radius` on `centre` and its apex `height` above it.
+pub struct Cone {
+    pub centre: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub phi_max: f64,
+    pub mat_ptr: Rc<dyn Material>,
+}
+
+impl Cone {
+    pub fn from(
+        centre: Point3,
+        radius: f64,
+        height: f64,
+        phi_max: f64,
+        m: Rc<dyn Material>,
+    ) -> Self {
+        Self {
+            centre,
+            radius,
+            height,
+            phi_max: degrees_to_radians(phi_max.clamp(0., 360.)),
+            mat_ptr: m,
+        }
+    }
+
+    // (radius / height)^2, so that f(y) = k (height - y)^2.
+    fn k(&self) -> f64 {
+        (self.radius / self.height).powi(2)
+    }
+}
+
+impl Quadric for Cone {
+    fn centre(&self) -> Point3 {
+        self.centre
+    }
+    fn mat_ptr(&self) -> Rc<dyn Material> {
+        self.mat_ptr.clone()
+    }
+    fn y_range(&self) -> (f64, f64) {
+        (0., self.height)
+    }
+    fn phi_max(&self) -> f64 {
+        self.phi_max
+    }
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64) {
+        let k = self.k();
+        let h = self.height - o.y();
+        (
+            d.x() * d.x() + d.z() * d.z() - k * d.y() * d.y(),
+            2. * (o.x() * d.x() + o.z() * d.z() + k * h * d.y()),
+            o.x() * o.x() + o.z() * o.z() - k * h * h,
+        )
+    }
+    fn f_prime(&self, y: f64) -> f64 {
+        -2. * self.k() * (self.height - y)
+    }
+    fn max_radius(&self) -> f64 {
+        self.radius
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.hit_surface(r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.quadric_bounding_box())
+    }
+}
+
+// A paraboloid with its vertex on `centre`, opening upwards to `radius` at `height`.
+pub struct Paraboloid {
+    pub centre: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub phi_max: f64,
+    pub mat_ptr: Rc<dyn Material>,
+}
+
+impl Paraboloid {
+    pub fn from(
+        centre: Point3,
+        radius: f64,
+        height: f64,
+        phi_max: f64,
+        m: Rc<dyn Material>,
+    ) -> Self {
+        Self {
+            centre,
+            radius,
+            height,
+            phi_max: degrees_to_radians(phi_max.clamp(0., 360.)),
+            mat_ptr: m,
+        }
+    }
+
+    // radius^2 / height, so that f(y) = k y.
+    fn k(&self) -> f64 {
+        self.radius * self.radius / self.height
+    }
+}
+
+impl Quadric for Paraboloid {
+    fn centre(&self) -> Point3 {
+        self.centre
+    }
+    fn mat_ptr(&self) -> Rc<dyn Material> {
+        self.mat_ptr.clone()
+    }
+    fn y_range(&self) -> (f64, f64) {
+        (0., self.height)
+    }
+    fn phi_max(&self) -> f64 {
+        self.phi_max
+    }
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64) {
+        let k = self.k();
+        (
+            d.x() * d.x() + d.z() * d.z(),
+            2. * (o.x() * d.x() + o.z() * d.z()) - k * d.y(),
+            o.x() * o.x() + o.z() * o.z() - k * o.y(),
+        )
+    }
+    fn f_prime(&self, _y: f64) -> f64 {
+        self.k()
+    }
+    fn max_radius(&self) -> f64 {
+        self.radius
+    }
+}
+
+impl Hittable for Paraboloid {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.hit_surface(r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.quadric_bounding_box())
+    }
+}
+
+// A hyperboloid of one sheet, narrowest at `centre` with `waist_radius` and widening to
+// `end_radius` at `half_height` above and below it.
+pub struct Hyperboloid {
+    pub centre: Point3,
+    pub waist_radius: f64,
+    pub end_radius: f64,
+    pub half_height: f64,
+    pub phi_max: f64,
+    pub mat_ptr: Rc<dyn Material>,
+}
+
+impl Hyperboloid {
+    pub fn from(
+        centre: Point3,
+        waist_radius: f64,
+        end_radius: f64,
+        half_height: f64,
+        phi_max: f64,
+        m: Rc<dyn Material>,
+    ) -> Self {
+        assert!(
+            end_radius >= waist_radius,
+            "A hyperboloid's ends can't be narrower than its waist"
+        );
+        Self {
+            centre,
+            waist_radius,
+            end_radius,
+            half_height,
+            phi_max: degrees_to_radians(phi_max.clamp(0., 360.)),
+            mat_ptr: m,
+        }
+    }
+
+    // (end_radius^2 - waist_radius^2) / half_height^2, so that f(y) = waist_radius^2 + k y^2.
+    fn k(&self) -> f64 {
+        (self.end_radius.powi(2) - self.waist_radius.powi(2)) / self.half_height.powi(2)
+    }
+}
+
+impl Quadric for Hyperboloid {
+    fn centre(&self) -> Point3 {
+        self.centre
+    }
+    fn mat_ptr(&self) -> Rc<dyn Material> {
+        self.mat_ptr.clone()
+    }
+    fn y_range(&self) -> (f64, f64) {
+        (-self.half_height, self.half_height)
+    }
+    fn phi_max(&self) -> f64 {
+        self.phi_max
+    }
+    fn coefficients(&self, o: Vec3, d: Vec3) -> (f64, f64, f64) {
+        let k = self.k();
+        (
+            d.x() * d.x() + d.z() * d.z() - k * d.y() * d.y(),
+            2. * (o.x() * d.x() + o.z() * d.z() - k * o.y() * d.y()),
+            o.x() * o.x() + o.z() * o.z() - k * o.y() * o.y() - self.waist_radius.powi(2),
+        )
+    }
+    fn f_prime(&self, y: f64) -> f64 {
+        2. * self.k() * y
+    }
+    fn max_radius(&self) -> f64 {
+        self.end_radius
+    }
+}
+
+impl Hittable for Hyperboloid {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.hit_surface(r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.quadric_bounding_box())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::material::Lambertian, vec3::Colour};
+
+    fn grey() -> Rc<dyn Material> {
+        Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)))
+    }
+
+    fn cylinder(phi_max: f64, capped: bool) -> Cylinder {
+        Cylinder::from(Point3::new(), 1., (0., 2.), phi_max, capped, grey())
+    }
+
+    fn hit(object: &impl Hittable, origin: Point3, direction: Vec3) -> Option<HitRecord> {
+        let mut rec = HitRecord::new();
+        if object.hit(Ray::from(origin, direction), 1e-9, f64::INFINITY, &mut rec) {
+            Some(rec)
+        } else {
+            None
+        }
+    }
+
+    fn assert_hit(rec: Option<HitRecord>, t: f64, normal: Vec3, front_face: bool) {
+        let rec = rec.expect("the ray should hit");
+        assert!((rec.t - t).abs() < 1e-9, "t = {}, expected {}", rec.t, t);
+        assert!(
+            (rec.normal - normal.unit_vector()).length() < 1e-9,
+            "normal = {:?}, expected {:?}",
+            rec.normal,
+            normal.unit_vector()
+        );
+        assert_eq!(rec.front_face, front_face);
+    }
+
+    #[test]
+    fn cylinder_hits_from_outside_and_inside() {
+        let tube = cylinder(360., false);
+        let across = Vec3::from(1., 0., 0.);
+        assert_hit(
+            hit(&tube, Point3::from(-5., 1., 0.), across),
+            4.,
+            Vec3::from(-1., 0., 0.),
+            true,
+        );
+        assert_hit(
+            hit(&tube, Point3::from(0., 1., 0.), across),
+            1.,
+            Vec3::from(-1., 0., 0.),
+            false,
+        );
+        // Straight down the open tube.
+        assert!(hit(&tube, Point3::from(0., 5., 0.), Vec3::from(0., -1., 0.)).is_none());
+        assert_hit(
+            hit(
+                &cylinder(360., true),
+                Point3::from(0., 5., 0.),
+                Vec3::from(0., -1., 0.),
+            ),
+            3.,
+            Vec3::from(0., 1., 0.),
+            true,
+        );
+    }
+
+    #[test]
+    fn cone_hits_its_side_and_apex() {
+        let cone = Cone::from(Point3::new(), 1., 1., 360., grey());
+        assert_hit(
+            hit(&cone, Point3::from(-5., 0.5, 0.), Vec3::from(1., 0., 0.)),
+            4.5,
+            Vec3::from(-1., 1., 0.),
+            true,
+        );
+        assert_hit(
+            hit(&cone, Point3::from(0., 5., 0.), Vec3::from(0., -1., 0.)),
+            4.,
+            Vec3::from(0., 1., 0.),
+            true,
+        );
+    }
+
+    #[test]
+    fn paraboloid_is_hit_inside_its_bowl() {
+        let bowl = Paraboloid::from(Point3::new(), 1., 1., 360., grey());
+        assert_hit(
+            hit(&bowl, Point3::from(0., 5., 0.), Vec3::from(0., -1., 0.)),
+            5.,
+            Vec3::from(0., 1., 0.),
+            false,
+        );
+        assert_hit(
+            hit(&bowl, Point3::from(-5., 0.25, 0.), Vec3::from(1., 0., 0.)),
+            4.5,
+            Vec3::from(-1., -1., 0.),
+            true,
+        );
+    }
+
+    #[test]
+    fn hyperboloid_hits_its_waist() {
+        let hyperboloid = Hyperboloid::from(Point3::new(), 0.5, 1., 1., 360., grey());
+        assert_hit(
+            hit(
+                &hyperboloid,
+                Point3::from(-5., 0., 0.),
+                Vec3::from(1., 0., 0.),
+            ),
+            4.5,
+            Vec3::from(-1., 0., 0.),
+            true,
+        );
+    }
+
+    #[test]
+    fn phi_max_cuts_away_the_rest_of_the_sweep() {
+        // Half a tube, on the +z side.
+        let half = cylinder(180., false);
+        let along = Vec3::from(0., 0., 1.);
+        assert_hit(
+            hit(&half, Point3::from(0., 1., -5.), along),
+            6.,
+            Vec3::from(0., 0., -1.),
+            false,
+        );
+        assert!(hit(&half, Point3::from(0., 1., -5.), along)
+            .is_some_and(|rec| (rec.u - 0.5).abs() < 1e-9));
+        assert_hit(
+            hit(&half, Point3::from(0., 1., 5.), -along),
+            4.,
+            Vec3::from(0., 0., 1.),
+            true,
+        );
+    }
+
+    #[test]
+    fn capped_cylinder_spans_its_inside() {
+        let closed = cylinder(360., true);
+        let r = Ray::from(Point3::from(-5., 1., 0.), Vec3::from(1., 0., 0.));
+        let spans = closed.spans(r).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].enter.t - 4.).abs() < 1e-9);
+        assert!((spans[0].exit.t - 6.).abs() < 1e-9);
+
+        // Through both caps.
+        let r = Ray::from(Point3::from(0.5, 3., 0.), Vec3::from(0., -1., 0.));
+        let spans = closed.spans(r).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].enter.t - 1.).abs() < 1e-9);
+        assert!((spans[0].exit.t - 3.).abs() < 1e-9);
+        assert!((spans[0].enter.normal - Vec3::from(0., 1., 0.)).length() < 1e-9);
+        assert!(spans[0].enter.front_face && !spans[0].exit.front_face);
+
+        // Passing beside it.
+        let r = Ray::from(Point3::from(-5., 1., 2.), Vec3::from(1., 0., 0.));
+        assert!(closed.spans(r).unwrap().is_empty());
+
+        assert!(cylinder(360., false).spans(r).is_none());
+        assert!(cylinder(180., true).spans(r).is_none());
+    }
+}
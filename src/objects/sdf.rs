@@ -0,0 +1,484 @@
+use crate::{
+    material::material::Material,
+    objects::{
+        aabb::Aabb,
+        hittable::{spans_from_crossings, HitRecord, Hittable, Span},
+    },
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+use std::{f64::consts::PI, rc::Rc};
+
+// A signed distance field built from primitives centred on the origin and operations on them.
+// Distances are negative inside.
+#[derive(Clone)]
+pub enum Sdf {
+    Sphere {
+        radius: f64,
+    },
+    Cuboid {
+        half_extents: Vec3,
+    },
+    // A box of the given outer size with its edges rounded off to `radius`.
+    RoundedBox {
+        half_extents: Vec3,
+        radius: f64,
+    },
+    // A ring around the y axis.
+    Torus {
+        major_radius: f64,
+        minor_radius: f64,
+    },
+    Translate {
+        offset: Vec3,
+        sdf: Box<Sdf>,
+    },
+    Union(Box<Sdf>, Box<Sdf>),
+    // A union that blends the shapes together where they come within `k` of each other.
+    SmoothUnion {
+        a: Box<Sdf>,
+        b: Box<Sdf>,
+        k: f64,
+    },
+    // Copies of the shape `spacing` apart along each axis, out to `limit` copies either side of
+    // the original. A zero spacing leaves that axis alone.
+    Repeat {
+        spacing: Vec3,
+        limit: Vec3,
+        sdf: Box<Sdf>,
+    },
+    // Turns the shape about the y axis by `rate` radians per unit of height.
+    Twist {
+        rate: f64,
+        sdf: Box<Sdf>,
+    },
+}
+
+impl Sdf {
+    pub fn sphere(radius: f64) -> Self {
+        Self::Sphere { radius }
+    }
+
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Self::Cuboid { half_extents }
+    }
+
+    pub fn rounded_box(half_extents: Vec3, radius: f64) -> Self {
+        Self::RoundedBox {
+            half_extents,
+            radius,
+        }
+    }
+
+    pub fn torus(major_radius: f64, minor_radius: f64) -> Self {
+        Self::Torus {
+            major_radius,
+            minor_radius,
+        }
+    }
+
+    pub fn translate(self, offset: Vec3) -> Self {
+        Self::Translate {
+            offset,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Self, k: f64) -> Self {
+        Self::SmoothUnion {
+            a: Box::new(self),
+            b: Box::new(other),
+            k,
+        }
+    }
+
+    pub fn repeat(self, spacing: Vec3, limit: Vec3) -> Self {
+        Self::Repeat {
+            spacing,
+            limit,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn twist(self, rate: f64) -> Self {
+        Self::Twist {
+            rate,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn distance(&self, p: Point3) -> f64 {
+        match self {
+            Self::Sphere { radius } => p.length() - radius,
+            Self::Cuboid { half_extents } => box_distance(p, *half_extents),
+            Self::RoundedBox {
+                half_extents,
+                radius,
+            } => {
+                let inner = *half_extents - Vec3::from(*radius, *radius, *radius);
+                box_distance(p, inner) - radius
+            }
+            Self::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let ring = (p.x() * p.x() + p.z() * p.z()).sqrt() - major_radius;
+                (ring * ring + p.y() * p.y()).sqrt() - minor_radius
+            }
+            Self::Translate { offset, sdf } => sdf.distance(p - *offset),
+            Self::Union(a, b) => a.distance(p).min(b.distance(p)),
+            Self::SmoothUnion { a, b, k } => {
+                let (da, db) = (a.distance(p), b.distance(p));
+                // Without room to blend this is a plain union, and the blend would divide by zero.
+                if *k <= 0. {
+                    return da.min(db);
+                }
+                let h = (k - (da - db).abs()).max(0.) / k;
+                da.min(db) - h * h * k * 0.25
+            }
+            Self::Repeat {
+                spacing,
+                limit,
+                sdf,
+            } => {
+                let fold = |x: f64, s: f64, l: f64| {
+                    if s > 0. {
+                        x - s * (x / s).round().clamp(-l, l)
+                    } else {
+                        x
+                    }
+                };
+                sdf.distance(Vec3::from(
+                    fold(p.x(), spacing.x(), limit.x()),
+                    fold(p.y(), spacing.y(), limit.y()),
+                    fold(p.z(), spacing.z(), limit.z()),
+                ))
+            }
+            Self::Twist { rate, sdf } => {
+                let (sin, cos) = (rate * p.y()).sin_cos();
+                sdf.distance(Vec3::from(
+                    cos * p.x() - sin * p.z(),
+                    p.y(),
+                    sin * p.x() + cos * p.z(),
+                ))
+            }
+        }
+    }
+
+    // A box holding the surface.
+    pub fn bounds(&self) -> Aabb {
+        match self {
+            Self::Sphere { radius } => {
+                let r = Vec3::from(*radius, *radius, *radius);
+                Aabb::from(-r, r)
+            }
+            Self::Cuboid { half_extents } | Self::RoundedBox { half_extents, .. } => {
+                Aabb::from(-*half_extents, *half_extents)
+            }
+            Self::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let extent = major_radius + minor_radius;
+                let half = Vec3::from(extent, *minor_radius, extent);
+                Aabb::from(-half, half)
+            }
+            Self::Translate { offset, sdf } => {
+                let inner = sdf.bounds();
+                Aabb::from(inner.minimum + *offset, inner.maximum + *offset)
+            }
+            Self::Union(a, b) => Aabb::surrounding(a.bounds(), b.bounds()),
+            Self::SmoothUnion { a, b, k } => {
+                // The blend never swells the union by more than k / 4.
+                let both = Aabb::surrounding(a.bounds(), b.bounds());
+                let swell = Vec3::from(1., 1., 1.) * (k.max(0.) * 0.25);
+                Aabb::from(both.minimum - swell, both.maximum + swell)
+            }
+            Self::Repeat {
+                spacing,
+                limit,
+                sdf,
+            } => {
+                let inner = sdf.bounds();
+                let reach = *spacing * *limit;
+                Aabb::from(inner.minimum - reach, inner.maximum + reach)
+            }
+            Self::Twist { sdf, .. } => {
+                let inner = sdf.bounds();
+                let radius = radial_extent(&inner);
+                Aabb::from(
+                    Point3::from(-radius, inner.minimum.y(), -radius),
+                    Point3::from(radius, inner.maximum.y(), radius),
+                )
+            }
+        }
+    }
+
+    // How much faster than distance the field can change. Tracing divides each step by this so
+    // that operations which distort space can't step through the surface.
+    pub fn lipschitz(&self) -> f64 {
+        match self {
+            Self::Translate { sdf, .. } | Self::Repeat { sdf, .. } => sdf.lipschitz(),
+            Self::Union(a, b) | Self::SmoothUnion { a, b, .. } => a.lipschitz().max(b.lipschitz()),
+            Self::Twist { rate, sdf } => {
+                let shear = rate * radial_extent(&sdf.bounds());
+                sdf.lipschitz() * (1. + shear * shear).sqrt()
+            }
+            _ => 1.,
+        }
+    }
+}
+
+fn box_distance(p: Point3, half_extents: Vec3) -> f64 {
+    let q = Vec3::from(
+        p.x().abs() - half_extents.x(),
+        p.y().abs() - half_extents.y(),
+        p.z().abs() - half_extents.z(),
+    );
+    let outside = Vec3::from(q.x().max(0.), q.y().max(0.), q.z().max(0.)).length();
+    let inside = q.x().max(q.y()).max(q.z()).min(0.);
+    outside + inside
+}
+
+// Furthest a box reaches from the y axis.
+fn radial_extent(bounds: &Aabb) -> f64 {
+    bounds
+        .corners()
+        .iter()
+        .map(|c| (c.x() * c.x() + c.z() * c.z()).sqrt())
+        .fold(0., f64::max)
+}
+
+const MAX_STEPS: usize = 512;
+// Distance at which a point counts as on the surface.
+const HIT_DISTANCE: f64 = 1e-6;
+// Smallest step taken, so that rays grazing the surface don't stall.
+const MIN_STEP: f64 = 1e-4;
+// Offset used to estimate the gradient.
+const GRADIENT_STEP: f64 = 1e-5;
+
+// Renders a signed distance field by sphere tracing: stepping along the ray by the distance to
+// the surface, which can't overshoot it. Crossings are found by the field changing sign and then
+// refined by bisection, which also lets rays that start inside find their way out. Surface
+// coordinates follow the direction of the normal, as on a sphere.
+pub struct SdfObject {
+    pub sdf: Sdf,
+    pub mat_ptr: Rc<dyn Material>,
+    bounds: Aabb,
+    lipschitz: f64,
+}
+
+impl SdfObject {
+    pub fn from(sdf: Sdf, m: Rc<dyn Material>) -> Self {
+        // Pad the bounds so that the gradient is never estimated outside them.
+        let inner = sdf.bounds();
+        let pad = Vec3::from(1., 1., 1.) * (10. * MIN_STEP);
+        Self {
+            bounds: Aabb::from(inner.minimum - pad, inner.maximum + pad),
+            lipschitz: sdf.lipschitz(),
+            sdf,
+            mat_ptr: m,
+        }
+    }
+
+    // Nearest crossing of the surface along the unit-speed ray from `start` to `end`.
+    fn march(&self, origin: Point3, d: Vec3, start: f64, end: f64) -> Option<f64> {
+        let mut t = start;
+        let mut distance = self.sdf.distance(origin + d * t);
+        let inside = distance < 0.;
+
+        for _ in 0..MAX_STEPS {
+            let step = (distance.abs() / self.lipschitz).max(MIN_STEP);
+            let next = t + step;
+            if next > end {
+                return None;
+            }
+            let next_distance = self.sdf.distance(origin + d * next);
+            if (next_distance < 0.) != inside {
+                return Some(self.refine(origin, d, t, next, inside));
+            }
+            if next_distance.abs() < HIT_DISTANCE {
+                return Some(next);
+            }
+            t = next;
+            distance = next_distance;
+        }
+        None
+    }
+
+    // Bisects [a, b] down to where the field changes sign, given which side `a` is on.
+    fn refine(&self, origin: Point3, d: Vec3, mut a: f64, mut b: f64, inside: bool) -> f64 {
+        while b - a > HIT_DISTANCE {
+            let mid = 0.5 * (a + b);
+            if (self.sdf.distance(origin + d * mid) < 0.) == inside {
+                a = mid;
+            } else {
+                b = mid;
+            }
+        }
+        0.5 * (a + b)
+    }
+
+    // First crossing of the surface within [t_min, t_max].
+    fn next_crossing(&self, r: Ray, t_min: f64, t_max: f64) -> Option<f64> {
+        let (t_enter, t_exit) = self.bounds.interval(r, t_min, t_max)?;
+        // March at unit speed from where the ray enters the bounds.
+        let length = r.direction().length();
+        let d = r.direction() / length;
+        let s = self.march(r.at(t_enter), d, 0., (t_exit - t_enter) * length)?;
+        Some(t_enter + s / length)
+    }
+
+    fn set_record(&self, r: Ray, t: f64, rec: &mut HitRecord) {
+        let n = self.normal(r.at(t));
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.set_face_normal(r, n);
+
+        let theta = (-n.y()).clamp(-1., 1.).acos();
+        let phi = (-n.z()).atan2(n.x()) + PI;
+        rec.u = phi / (2. * PI);
+        rec.v = theta / PI;
+        rec.dpdu = Vec3::from(n.z(), 0., -n.x()) * (2. * PI);
+        let sin_theta = theta.sin();
+        rec.dpdv = if sin_theta > 1e-8 {
+            Vec3::from(
+                -n.x() * n.y() / sin_theta,
+                sin_theta,
+                -n.z() * n.y() / sin_theta,
+            ) * PI
+        } else {
+            Vec3::new()
+        };
+        rec.mat_ptr = self.mat_ptr.clone();
+    }
+
+    // Tetrahedral estimate of the field's gradient, as in Quilez's "Normals for an SDF".
+    fn normal(&self, p: Point3) -> Vec3 {
+        [
+            Vec3::from(1., -1., -1.),
+            Vec3::from(-1., -1., 1.),
+            Vec3::from(-1., 1., -1.),
+            Vec3::from(1., 1., 1.),
+        ]
+        .iter()
+        .map(|&k| k * self.sdf.distance(p + k * GRADIENT_STEP))
+        .sum::<Vec3>()
+        .unit_vector()
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let mut from = t_min;
+        while let Some(t) = self.next_crossing(r, from, t_max) {
+            self.set_record(r, t, rec);
+            if rec.mat_ptr.accepts_hit(rec) {
+                return true;
+            }
+            // Carry on past a surface the material lets through.
+            from = t + MIN_STEP / r.direction().length();
+        }
+        false
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+
+    fn spans(&self, r: Ray) -> Option<Vec<Span>> {
+        // The padded bounds start outside the surface, so crossings alternate between entering
+        // and leaving.
+        let mut crossings = vec![];
+        let mut from = f64::NEG_INFINITY;
+        while let Some(t) = self.next_crossing(r, from, f64::INFINITY) {
+            crossings.push(t);
+            from = t + MIN_STEP / r.direction().length();
+        }
+        Some(spans_from_crossings(&crossings, |t, rec| {
+            self.set_record(r, t, rec)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::Lambertian,
+        objects::{sphere::Sphere, torus::Torus},
+        vec3::{dot, Colour},
+    };
+
+    fn grey() -> Rc<dyn Material> {
+        Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)))
+    }
+
+    // Rays from all around the origin towards points scattered about it, some missing.
+    fn probe_rays() -> Vec<Ray> {
+        let mut rays = vec![];
+        for view in 0..8 {
+            let azimuth = 2. * PI * view as f64 / 8. + 0.1;
+            let elevation = 0.3 * (view as f64 - 3.5);
+            let origin = Point3::from(
+                4. * elevation.cos() * azimuth.cos(),
+                4. * elevation.sin(),
+                4. * elevation.cos() * azimuth.sin(),
+            );
+            for i in 0..5 {
+                for j in 0..5 {
+                    let target = Point3::from(0.35 * (i as f64 - 2.), 0.2 * (j as f64 - 2.), 0.1);
+                    rays.push(Ray::from(origin, target - origin));
+                }
+            }
+        }
+        rays
+    }
+
+    // Checks that sphere tracing finds the same hits as the analytic surface.
+    fn assert_matches(traced: &SdfObject, analytic: &impl Hittable) {
+        let mut hits = 0;
+        for r in probe_rays() {
+            let (mut expected, mut actual) = (HitRecord::new(), HitRecord::new());
+            let hit = analytic.hit(r, 1e-3, f64::INFINITY, &mut expected);
+            assert_eq!(traced.hit(r, 1e-3, f64::INFINITY, &mut actual), hit);
+            if hit {
+                hits += 1;
+                assert!((actual.t - expected.t).abs() < 1e-5);
+                assert!(dot(actual.normal, expected.normal) > 1. - 1e-6);
+                assert_eq!(actual.front_face, expected.front_face);
+            }
+        }
+        assert!(hits > 0);
+    }
+
+    #[test]
+    fn traced_sphere_matches_analytic_sphere() {
+        let traced = SdfObject::from(Sdf::sphere(0.7), grey());
+        assert_matches(&traced, &Sphere::from(Point3::new(), 0.7, grey()));
+    }
+
+    #[test]
+    fn traced_torus_matches_analytic_torus() {
+        let traced = SdfObject::from(Sdf::torus(0.6, 0.25), grey());
+        assert_matches(&traced, &Torus::from(Point3::new(), 0.6, 0.25, grey()));
+    }
+
+    #[test]
+    fn smooth_union_without_blend_is_a_union() {
+        let a = Sdf::sphere(0.5).translate(Vec3::from(-0.4, 0., 0.));
+        let b = Sdf::sphere(0.5).translate(Vec3::from(0.4, 0., 0.));
+        let union = a.clone().union(b.clone());
+        for k in [0., -1.] {
+            let smooth = a.clone().smooth_union(b.clone(), k);
+            for x in [-1., -0.4, 0., 0.3, 1.2] {
+                let p = Point3::from(x, 0.1, 0.);
+                assert_eq!(smooth.distance(p), union.distance(p));
+            }
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use crate::{
     material::material::Material,
-    objects::hittable::{HitRecord, Hittable},
+    objects::{
+        aabb::Aabb,
+        hittable::{spans_from_crossings, HitRecord, Hittable, Span},
+    },
     ray::Ray,
-    vec3::{dot, Point3},
+    vec3::{dot, Point3, Vec3},
 };
-use std::rc::Rc;
+use std::{f64::consts::PI, rc::Rc};
 
 pub struct Sphere {
     pub centre: Point3,
@@ -20,6 +23,35 @@ impl Sphere {
             mat_ptr: m,
         }
     }
+
+    fn set_record(&self, r: Ray, t: f64, rec: &mut HitRecord) {
+        rec.t = t;
+        rec.p = r.at(rec.t);
+        let outward_normal = (rec.p - self.centre) / self.radius;
+        rec.set_face_normal(r, outward_normal);
+        self.set_surface_coordinates(outward_normal, rec);
+        rec.mat_ptr = self.mat_ptr.clone();
+    }
+
+    // Sets u from the angle around the y axis, starting at -x, and v from the angle down from -y.
+    fn set_surface_coordinates(&self, n: Vec3, rec: &mut HitRecord) {
+        let theta = (-n.y()).clamp(-1., 1.).acos();
+        let phi = (-n.z()).atan2(n.x()) + PI;
+        rec.u = phi / (2. * PI);
+        rec.v = theta / PI;
+
+        let sin_theta = theta.sin();
+        rec.dpdu = Vec3::from(n.z(), 0., -n.x()) * (2. * PI * self.radius);
+        rec.dpdv = if sin_theta > 1e-8 {
+            Vec3::from(
+                -n.x() * n.y() / sin_theta,
+                sin_theta,
+                -n.z() * n.y() / sin_theta,
+            ) * (PI * self.radius)
+        } else {
+            Vec3::new()
+        };
+    }
 }
 
 impl Hittable for Sphere {
@@ -33,20 +65,40 @@ impl Hittable for Sphere {
             return false;
         }
 
-        let mut root = (-half_b - discriminant.sqrt()) / a;
-        if root < t_min || t_max < root {
-            root = (-half_b + discriminant.sqrt()) / a;
+        let sqrtd = discriminant.sqrt();
+        for root in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
             if root < t_min || t_max < root {
-                return false;
+                continue;
+            }
+
+            self.set_record(r, root, rec);
+            if self.mat_ptr.accepts_hit(rec) {
+                return true;
             }
         }
 
-        rec.t = root;
-        rec.p = r.at(rec.t);
-        let outward_normal = (rec.p - self.centre) / self.radius;
-        rec.set_face_normal(r, outward_normal);
-        rec.mat_ptr = self.mat_ptr.clone();
+        false
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = Vec3::from(self.radius, self.radius, self.radius);
+        Some(Aabb::from(self.centre - extent, self.centre + extent))
+    }
+
+    fn spans(&self, r: Ray) -> Option<Vec<Span>> {
+        let oc = r.origin() - self.centre;
+        let a = r.direction().squared();
+        let half_b = dot(oc, r.direction());
+        let c = oc.squared() - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+        if discriminant <= 0. {
+            return Some(vec![]);
+        }
 
-        true
+        let sqrtd = discriminant.sqrt();
+        let crossings = [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a];
+        Some(spans_from_crossings(&crossings, |t, rec| {
+            self.set_record(r, t, rec)
+        }))
     }
 }
@@ -0,0 +1,384 @@
+use crate::{
+    material::material::Material,
+    objects::{
+        aabb::Aabb,
+        hittable::{spans_from_crossings, HitRecord, Hittable, Span},
+    },
+    polynomial::roots_in,
+    ray::Ray,
+    vec3::{dot, Point3, Vec3},
+};
+use std::{f64::consts::PI, rc::Rc};
+
+// A ring around the vertical axis through `centre`: the points `minor_radius` away from the circle
+// of `major_radius` in the horizontal plane. u goes around the axis from +x towards +z and v
+// around the tube, starting on its outer equator and turning upwards.
+pub struct Torus {
+    pub centre: Point3,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub mat_ptr: Rc<dyn Material>,
+}
+
+impl Torus {
+    pub fn from(centre: Point3, major_radius: f64, minor_radius: f64, m: Rc<dyn Material>) -> Self {
+        Self {
+            centre,
+            major_radius,
+            minor_radius,
+            mat_ptr: m,
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        let extent = self.major_radius + self.minor_radius;
+        let half = Vec3::from(extent, self.minor_radius, extent);
+        Aabb::from(self.centre - half, self.centre + half)
+    }
+
+    // Coefficients of the torus's quartic for a ray with local origin `o` and unit direction `d`.
+    fn coefficients(&self, o: Vec3, d: Vec3) -> [f64; 5] {
+        let r2 = self.major_radius * self.major_radius;
+        let od = dot(o, d);
+        let k = dot(o, o) + r2 - self.minor_radius * self.minor_radius;
+        let d_xz = d.x() * d.x() + d.z() * d.z();
+        let od_xz = o.x() * d.x() + o.z() * d.z();
+        let o_xz = o.x() * o.x() + o.z() * o.z();
+        [
+            k * k - 4. * r2 * o_xz,
+            4. * od * k - 8. * r2 * od_xz,
+            2. * k + 4. * od * od - 4. * r2 * d_xz,
+            4. * od,
+            1.,
+        ]
+    }
+
+    // Values of t where the ray crosses the surface, in order, found at least over
+    // [t_min, t_max].
+    fn crossings(&self, r: Ray, t_min: f64, t_max: f64) -> Vec<f64> {
+        // Solving from where the ray enters the bounding box keeps the coefficients small for
+        // distant rays, and the box limits the range to search. The torus touches the box, so
+        // the range is padded to keep roots on its faces from being lost to rounding.
+        let (t_enter, t_exit) = match self.bounds().interval(r, t_min, t_max) {
+            Some(interval) => interval,
+            None => return vec![],
+        };
+        let length = r.direction().length();
+        let d = r.direction() / length;
+        let o = r.at(t_enter) - self.centre;
+        let pad = self.minor_radius;
+        roots_in(
+            &self.coefficients(o, d),
+            -pad,
+            (t_exit - t_enter) * length + pad,
+        )
+        .iter()
+        .map(|s| t_enter + s / length)
+        .collect()
+    }
+
+    fn set_record(&self, r: Ray, t: f64, rec: &mut HitRecord) {
+        let p = r.at(t) - self.centre;
+        let ring = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let core = if ring > 0. {
+            Vec3::from(p.x(), 0., p.z()) * (self.major_radius / ring)
+        } else {
+            Vec3::new()
+        };
+        let outward_normal = (p - core).unit_vector();
+
+        let phi = angle(p.z(), p.x());
+        let theta = angle(p.y(), ring - self.major_radius);
+
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.set_face_normal(r, outward_normal);
+        rec.u = phi / (2. * PI);
+        rec.v = theta / (2. * PI);
+        rec.dpdu = Vec3::from(-p.z(), 0., p.x()) * (2. * PI);
+        rec.dpdv = Vec3::from(
+            -theta.sin() * phi.cos(),
+            theta.cos(),
+            -theta.sin() * phi.sin(),
+        ) * (2. * PI * self.minor_radius);
+        rec.mat_ptr = self.mat_ptr.clone();
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        for t in self.crossings(r, t_min, t_max) {
+            if t < t_min || t_max < t {
+                continue;
+            }
+            self.set_record(r, t, rec);
+            if rec.mat_ptr.accepts_hit(rec) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds())
+    }
+
+    fn spans(&self, r: Ray) -> Option<Vec<Span>> {
+        let crossings = self.crossings(r, f64::NEG_INFINITY, f64::INFINITY);
+        Some(spans_from_crossings(&crossings, |t, rec| {
+            self.set_record(r, t, rec)
+        }))
+    }
+}
+
+// atan2 mapped to [0, 2pi).
+fn angle(y: f64, x: f64) -> f64 {
+    let a = y.atan2(x);
+    if a < 0. {
+        a + 2. * PI
+    } else {
+        a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::material::Lambertian,
+        vec3::{cross, Colour},
+    };
+
+    const MAJOR: f64 = 1.;
+    const MINOR: f64 = 0.3;
+
+    fn torus() -> Torus {
+        let m = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        Torus::from(Point3::new(), MAJOR, MINOR, m)
+    }
+
+    fn point_on(phi: f64, theta: f64) -> Point3 {
+        let ring = MAJOR + MINOR * theta.cos();
+        Point3::from(ring * phi.cos(), MINOR * theta.sin(), ring * phi.sin())
+    }
+
+    // A dense triangulation of the torus, for reference.
+    struct Mesh {
+        triangles: Vec<[Point3; 3]>,
+    }
+
+    impl Mesh {
+        fn tessellate(around: usize, tube: usize) -> Self {
+            let vertex = |i: usize, j: usize| {
+                point_on(
+                    2. * PI * (i % around) as f64 / around as f64,
+                    2. * PI * (j % tube) as f64 / tube as f64,
+                )
+            };
+            let mut triangles = vec![];
+            for i in 0..around {
+                for j in 0..tube {
+                    let (a, b) = (vertex(i, j), vertex(i + 1, j));
+                    let (c, d) = (vertex(i + 1, j + 1), vertex(i, j + 1));
+                    triangles.push([a, b, c]);
+                    triangles.push([a, c, d]);
+                }
+            }
+            Self { triangles }
+        }
+
+        // Nearest hit as (t, unit face normal), by Möller-Trumbore.
+        fn hit(&self, r: Ray) -> Option<(f64, Vec3)> {
+            let mut nearest: Option<(f64, Vec3)> = None;
+            for [a, b, c] in &self.triangles {
+                let (e1, e2) = (*b - *a, *c - *a);
+                let pv = cross(r.direction(), e2);
+                let det = dot(e1, pv);
+                if det.abs() < 1e-14 {
+                    continue;
+                }
+                let tv = r.origin() - *a;
+                let u = dot(tv, pv) / det;
+                if !(0. ..=1.).contains(&u) {
+                    continue;
+                }
+                let qv = cross(tv, e1);
+                let v = dot(r.direction(), qv) / det;
+                if v < 0. || u + v > 1. {
+                    continue;
+                }
+                let t = dot(e2, qv) / det;
+                if t > 1e-9 && nearest.is_none_or(|(best, _)| t < best) {
+                    nearest = Some((t, cross(e2, e1).unit_vector()));
+                }
+            }
+            nearest
+        }
+    }
+
+    // Rays from a ring of viewpoints around and above the torus towards a grid of targets
+    // covering it.
+    fn probe_rays() -> Vec<Ray> {
+        let mut rays = vec![];
+        for view in 0..6 {
+            let azimuth = 2. * PI * view as f64 / 6. + 0.1;
+            let elevation = -0.6 + 0.25 * view as f64;
+            let eye = Point3::from(
+                4. * elevation.cos() * azimuth.cos(),
+                4. * elevation.sin(),
+                4. * elevation.cos() * azimuth.sin(),
+            );
+            for i in 0..24 {
+                for j in 0..24 {
+                    let target = Point3::from(
+                        -1.4 + 2.8 * (i as f64 + 0.5) / 24.,
+                        -0.4 + 0.8 * (j as f64 + 0.5) / 24.,
+                        -1.4 + 2.8 * ((i * 7 + j * 5) % 24) as f64 / 24.,
+                    );
+                    rays.push(Ray::from(eye, target - eye));
+                }
+            }
+        }
+        rays
+    }
+
+    fn hit(object: &Torus, r: Ray) -> Option<HitRecord> {
+        let mut rec = HitRecord::new();
+        if object.hit(r, 1e-9, f64::INFINITY, &mut rec) {
+            Some(rec)
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn agrees_with_tessellated_mesh() {
+        let object = torus();
+        let (around, tube) = (192, 96);
+        let mesh = Mesh::tessellate(around, tube);
+        // How far the facets can sit inside the surface.
+        let sagitta = (MAJOR + MINOR) * (1. - (PI / around as f64).cos())
+            + MINOR * (1. - (PI / tube as f64).cos());
+        let rays = probe_rays();
+        let mut compared = 0;
+        let mut disagreements = 0;
+
+        for r in rays.iter().copied() {
+            match (hit(&object, r), mesh.hit(r)) {
+                (Some(rec), Some((t, face_normal))) => {
+                    let d = r.direction().unit_vector();
+                    let outward = if rec.front_face {
+                        rec.normal
+                    } else {
+                        -rec.normal
+                    };
+                    // Near the silhouette the mesh may hit a different facet from the surface.
+                    let cos = dot(d, outward).abs();
+                    if cos < 0.2 {
+                        continue;
+                    }
+                    compared += 1;
+                    let length = r.direction().length();
+                    assert!(
+                        (rec.t - t).abs() * length < 2. * sagitta / cos,
+                        "distance {} against mesh {}",
+                        rec.t * length,
+                        t * length
+                    );
+                    assert!(
+                        dot(outward, face_normal) > 0.999,
+                        "normal {:?} against mesh {:?}",
+                        outward,
+                        face_normal
+                    );
+                }
+                (None, None) => {}
+                _ => disagreements += 1,
+            }
+        }
+
+        assert!(compared > rays.len() / 4, "only compared {} rays", compared);
+        // Only rays grazing the silhouette can disagree on whether there's a hit at all.
+        assert!(
+            disagreements * 100 < rays.len(),
+            "{} of {} rays disagree on hitting",
+            disagreements,
+            rays.len()
+        );
+    }
+
+    #[test]
+    fn surface_coordinates_match_hit_points() {
+        let object = torus();
+        for r in probe_rays() {
+            if let Some(rec) = hit(&object, r) {
+                let expected = point_on(2. * PI * rec.u, 2. * PI * rec.v);
+                assert!((rec.p - expected).length() < 1e-9);
+                assert!((0. ..1.).contains(&rec.u) && (0. ..1.).contains(&rec.v));
+
+                // The derivatives are tangent to the surface.
+                let outward = if rec.front_face {
+                    rec.normal
+                } else {
+                    -rec.normal
+                };
+                assert!(dot(rec.dpdu.unit_vector(), outward).abs() < 1e-9);
+                assert!(dot(rec.dpdv.unit_vector(), outward).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn faces_rays_from_inside_the_tube() {
+        let object = torus();
+        let inside = Point3::from(MAJOR, 0., 0.);
+
+        let rec = hit(&object, Ray::from(inside, Vec3::from(1., 0., 0.))).unwrap();
+        assert!(!rec.front_face);
+        assert!((rec.t - MINOR).abs() < 1e-12);
+        assert!((rec.normal - Vec3::from(-1., 0., 0.)).length() < 1e-12);
+
+        let outside = Point3::from(MAJOR + 1., 0., 0.);
+        let rec = hit(&object, Ray::from(outside, Vec3::from(-1., 0., 0.))).unwrap();
+        assert!(rec.front_face);
+        assert!((rec.t - (1. - MINOR)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn stays_accurate_for_distant_rays() {
+        let object = torus();
+        let distance = 1e5;
+
+        // Along the equator the nearest hit is the outside of the ring.
+        let r = Ray::from(Point3::from(distance, 0., 0.), Vec3::from(-1., 0., 0.));
+        let rec = hit(&object, r).unwrap();
+        assert!((rec.t - (distance - MAJOR - MINOR)).abs() < 1e-9);
+
+        // Straight down through the hole misses.
+        let r = Ray::from(Point3::from(0., distance, 0.), Vec3::from(0., -1., 0.));
+        assert!(hit(&object, r).is_none());
+
+        // Straight down through the tube hits its top.
+        let r = Ray::from(Point3::from(0., distance, MAJOR), Vec3::from(0., -1., 0.));
+        let rec = hit(&object, r).unwrap();
+        assert!((rec.t - (distance - MINOR)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn finds_all_four_crossings_in_order() {
+        let object = torus();
+        let r = Ray::from(Point3::from(-3., 0., 0.), Vec3::from(2., 0., 0.));
+        let mut t_min = 0.;
+        let mut crossings = vec![];
+        let mut rec = HitRecord::new();
+        while object.hit(r, t_min, f64::INFINITY, &mut rec) {
+            crossings.push(rec.p.x());
+            t_min = rec.t + 1e-9;
+        }
+        let expected = [-1.3, -0.7, 0.7, 1.3];
+        assert_eq!(crossings.len(), expected.len(), "crossings {:?}", crossings);
+        for (x, e) in crossings.iter().zip(expected) {
+            assert!((x - e).abs() < 1e-9, "crossings {:?}", crossings);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    objects::{
+        aabb::Aabb,
+        hittable::{HitRecord, Hittable, Span},
+    },
+    ray::Ray,
+    utils::degrees_to_radians,
+    vec3::Vec3,
+};
+use std::rc::Rc;
+
+// Places an object in the world by scaling it uniformly, rotating it about the y axis and then
+// translating it. Rays are moved into the object's space to be intersected, so `t` is the same in
+// both spaces.
+pub struct Transform {
+    pub object: Rc<dyn Hittable>,
+    pub translation: Vec3,
+    pub scale: f64,
+    sin_theta: f64,
+    cos_theta: f64,
+}
+
+impl Transform {
+    pub fn from(object: Rc<dyn Hittable>, translation: Vec3, rotation: f64, scale: f64) -> Self {
+        let theta = degrees_to_radians(rotation);
+        Self {
+            object,
+            translation,
+            scale,
+            sin_theta: theta.sin(),
+            cos_theta: theta.cos(),
+        }
+    }
+
+    fn rotate(&self, v: Vec3, sin_theta: f64) -> Vec3 {
+        Vec3::from(
+            self.cos_theta * v.x() + sin_theta * v.z(),
+            v.y(),
+            -sin_theta * v.x() + self.cos_theta * v.z(),
+        )
+    }
+
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        self.rotate(v, -self.sin_theta) / self.scale
+    }
+
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        self.rotate(v, self.sin_theta)
+    }
+
+    fn to_object_ray(&self, r: Ray) -> Ray {
+        Ray {
+            orig: self.to_object(r.origin() - self.translation),
+            dir: self.to_object(r.direction()),
+            ..r
+        }
+    }
+
+    // Moves a record of where the object was hit by the moved ray back to the world.
+    fn to_world_record(&self, r: Ray, rec: &mut HitRecord) {
+        // A uniform scale leaves normals pointing the same way, so only the rotation is undone.
+        let outward_normal = if rec.front_face {
+            rec.normal
+        } else {
+            -rec.normal
+        };
+        rec.p = r.at(rec.t);
+        rec.set_face_normal(r, self.to_world(outward_normal));
+        rec.dpdu = self.to_world(rec.dpdu) * self.scale;
+        rec.dpdv = self.to_world(rec.dpdv) * self.scale;
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        if !self.object.hit(self.to_object_ray(r), t_min, t_max, rec) {
+            return false;
+        }
+        self.to_world_record(r, rec);
+        true
+    }
+
+    fn object_count(&self) -> usize {
+        self.object.object_count()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let inner = self.object.bounding_box()?;
+        let corners = inner
+            .corners()
+            .map(|corner| self.to_world(corner) * self.scale + self.translation);
+        Some(
+            corners
+                .iter()
+                .fold(Aabb::from(corners[0], corners[0]), |total, &corner| {
+                    Aabb::surrounding(total, Aabb::from(corner, corner))
+                }),
+        )
+    }
+
+    fn spans(&self, r: Ray) -> Option<Vec<Span>> {
+        let mut spans = self.object.spans(self.to_object_ray(r))?;
+        for span in &mut spans {
+            for rec in [&mut span.enter, &mut span.exit] {
+                if rec.t.is_finite() {
+                    self.to_world_record(r, rec);
+                }
+            }
+        }
+        Some(spans)
+    }
+}
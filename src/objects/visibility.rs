@@ -0,0 +1,178 @@
+use crate::{
+    objects::{
+        aabb::Aabb,
+        hittable::{HitRecord, Hittable, Span},
+    },
+    ray::{Ray, RayKind},
+};
+use std::rc::Rc;
+
+// Which kinds of ray can see an object.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Visibility {
+    pub camera: bool,
+    pub shadow: bool,
+    pub reflection: bool,
+    pub refraction: bool,
+    pub diffuse: bool,
+}
+
+impl Visibility {
+    pub fn all() -> Self {
+        Self {
+            camera: true,
+            shadow: true,
+            reflection: true,
+            refraction: true,
+            diffuse: true,
+        }
+    }
+
+    pub fn visible_to(&self, kind: RayKind) -> bool {
+        match kind {
+            RayKind::Camera => self.camera,
+            RayKind::Shadow => self.shadow,
+            RayKind::Reflection => self.reflection,
+            RayKind::Refraction => self.refraction,
+            RayKind::Diffuse => self.diffuse,
+        }
+    }
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+// Wraps an object to hide it from some kinds of ray and to unlink it from some lights, which
+// then don't illuminate it.
+pub struct Visible {
+    pub object: Rc<dyn Hittable>,
+    pub visibility: Visibility,
+    // Bit `i` is set when the object is lit by light `i`.
+    pub light_mask: u64,
+}
+
+impl Visible {
+    pub fn from(object: Rc<dyn Hittable>) -> Self {
+        Self {
+            object,
+            visibility: Visibility::all(),
+            light_mask: u64::MAX,
+        }
+    }
+
+    pub fn hidden_from(mut self, kind: RayKind) -> Self {
+        match kind {
+            RayKind::Camera => self.visibility.camera = false,
+            RayKind::Shadow => self.visibility.shadow = false,
+            RayKind::Reflection => self.visibility.reflection = false,
+            RayKind::Refraction => self.visibility.refraction = false,
+            RayKind::Diffuse => self.visibility.diffuse = false,
+        }
+        self
+    }
+
+    pub fn excluding_light(mut self, light: usize) -> Self {
+        assert!(light < 64, "Only the first 64 lights can be unlinked");
+        self.light_mask &= !(1 << light);
+        self
+    }
+}
+
+impl Hittable for Visible {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        if !self.visibility.visible_to(r.kind) || !self.object.hit(r, t_min, t_max, rec) {
+            return false;
+        }
+        rec.light_mask &= self.light_mask;
+        true
+    }
+
+    fn object_count(&self) -> usize {
+        self.object.object_count()
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object.bounding_box()
+    }
+
+    fn spans(&self, r: Ray) -> Option<Vec<Span>> {
+        self.object.spans(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        light::{direct_lighting, PointLight},
+        material::material::Lambertian,
+        objects::{bvh::Bvh, hittable_list::HittableList, sphere::Sphere},
+        vec3::{Colour, Point3, Vec3},
+    };
+
+    fn sphere(centre: Point3) -> Rc<dyn Hittable> {
+        let m = Rc::new(Lambertian::from(Colour::from(0.5, 0.5, 0.5)));
+        Rc::new(Sphere::from(centre, 1., m))
+    }
+
+    fn hit(world: &dyn Hittable, r: Ray) -> Option<HitRecord> {
+        let mut rec = HitRecord::new();
+        if world.hit(r, 0.001, f64::INFINITY, &mut rec) {
+            Some(rec)
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn hidden_objects_are_skipped_by_the_rays_they_hide_from() {
+        // A camera-hidden sphere in front of a visible one.
+        let mut list = HittableList::new();
+        list.add(Rc::new(
+            Visible::from(sphere(Point3::new())).hidden_from(RayKind::Camera),
+        ));
+        list.add(sphere(Point3::from(0., 0., -5.)));
+        let bvh = Bvh::from(&list);
+
+        let r = Ray::from(Point3::from(0., 0., 5.), Vec3::from(0., 0., -1.));
+        for world in [&list as &dyn Hittable, &bvh] {
+            let rec = hit(world, r.with_kind(RayKind::Camera)).unwrap();
+            assert_eq!(rec.object_id, 1);
+            assert!((rec.t - 9.).abs() < 1e-9);
+
+            let rec = hit(world, r.with_kind(RayKind::Reflection)).unwrap();
+            assert_eq!(rec.object_id, 0);
+            assert!((rec.t - 4.).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn unlinked_lights_leave_objects_unlit() {
+        let lights = [
+            PointLight::from(Point3::from(0., 5., 0.), Colour::from(10., 10., 10.)),
+            PointLight::from(Point3::from(5., 0., 0.), Colour::from(10., 10., 10.)),
+        ];
+        // Only light 0 reaches the top of the sphere.
+        let r = Ray::from(Point3::from(0., 5., 0.), Vec3::from(0., -1., 0.));
+        let lighting = |object: Rc<dyn Hittable>| {
+            let mut world = HittableList::new();
+            world.add(object);
+            let rec = hit(&world, r).unwrap();
+            let diffuse = Colour::from(1., 1., 1.);
+            direct_lighting(&lights, &world, &rec, diffuse, None)
+        };
+
+        assert!(lighting(sphere(Point3::new())).x() > 0.);
+        let unlinked = Visible::from(sphere(Point3::new())).excluding_light(0);
+        assert_eq!(lighting(Rc::new(unlinked)).length(), 0.);
+        let other = Visible::from(sphere(Point3::new())).excluding_light(1);
+        assert!(lighting(Rc::new(other)).x() > 0.);
+    }
+}
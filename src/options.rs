@@ -0,0 +1,539 @@
+use crate::{
+    camera::{aperture::Aperture, lens_system::LensElement, panoramic::FisheyeMapping},
+    filter::{Filter, FilterKind},
+    image::StereoLayout,
+    light::PointLight,
+    ray::RayKind,
+    scenes::Scene,
+    tonemap::{ToneMapOperator, ToneMapper},
+    vec3::{Colour, Point3},
+    IMAGE_HEIGHT, IMAGE_WIDTH,
+};
+use std::{env, time::Duration};
+
+pub enum Command {
+    Render,
+    // Denoise an existing PFM image, optionally guided by albedo and normal PFM buffers.
+    Denoise {
+        input: String,
+        output: String,
+        albedo: Option<String>,
+        normal: Option<String>,
+    },
+}
+
+pub enum CameraKind {
+    Perspective,
+    Orthographic,
+    Equirectangular,
+    Fisheye(FisheyeMapping),
+    CubeMap,
+    // Traces rays through a lens prescription.
+    LensSystem(Vec<LensElement>),
+}
+
+impl CameraKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "perspective" => Some(Self::Perspective),
+            "orthographic" => Some(Self::Orthographic),
+            "equirectangular" => Some(Self::Equirectangular),
+            "fisheye-equidistant" => Some(Self::Fisheye(FisheyeMapping::Equidistant)),
+            "fisheye-equisolid" => Some(Self::Fisheye(FisheyeMapping::Equisolid)),
+            "cube-map" => Some(Self::CubeMap),
+            _ => None,
+        }
+    }
+}
+
+pub struct Options {
+    pub command: Command,
+    pub scene: Scene,
+    pub time_limit: Option<Duration>,
+    pub filter: Filter,
+    pub tone_mapper: ToneMapper,
+    pub aovs: bool,
+    pub denoise: bool,
+    pub camera: CameraKind,
+    pub fov: Option<f64>,
+    pub view_height: f64,
+    pub stereo: Option<StereoLayout>,
+    pub eye_separation: f64,
+    pub convergence_dist: Option<f64>,
+    pub aperture: Aperture,
+    pub cat_eye: f64,
+    pub sensor_width: f64,
+    pub focal_length: Option<f64>,
+    pub f_stop: f64,
+    pub shutter_speed: f64,
+    pub iso: f64,
+    pub focus_pixel: Option<(i32, i32)>,
+    pub shift: (f64, f64),
+    pub tilt: f64,
+    pub swing: f64,
+    pub frames: Option<(i32, i32)>,
+    pub lights: Vec<PointLight>,
+    // Object IDs paired with a kind of ray to hide them from, or a light to unlink them from.
+    pub hidden: Vec<(usize, RayKind)>,
+    pub unlinked: Vec<(usize, usize)>,
+    // Image maps for the ground, with the bump map's height scale and the opacity below which
+    // the ground is cut away instead of blended.
+    pub normal_map: Option<String>,
+    pub bump_map: Option<(String, f64)>,
+    pub opacity_map: Option<String>,
+    pub opacity_threshold: Option<f64>,
+    pub spectral: bool,
+    pub fps: f64,
+    pub resolution: Option<(i32, i32)>,
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Self {
+            command: Command::Render,
+            scene: Scene::Random,
+            time_limit: None,
+            filter: Filter::default(),
+            tone_mapper: ToneMapper::default(),
+            aovs: false,
+            denoise: false,
+            camera: CameraKind::Perspective,
+            fov: None,
+            view_height: 4.,
+            stereo: None,
+            eye_separation: 0.065,
+            convergence_dist: None,
+            aperture: Aperture::default(),
+            cat_eye: 0.,
+            sensor_width: 36.,
+            focal_length: None,
+            f_stop: 16.,
+            shutter_speed: 0.01,
+            iso: 100.,
+            focus_pixel: None,
+            shift: (0., 0.),
+            tilt: 0.,
+            swing: 0.,
+            frames: None,
+            lights: vec![],
+            hidden: vec![],
+            unlinked: vec![],
+            normal_map: None,
+            bump_map: None,
+            opacity_map: None,
+            opacity_threshold: None,
+            spectral: false,
+            fps: 24.,
+            resolution: None,
+        }
+    }
+
+    // Equirectangular images cover 360 by 180 degrees, so they default to a 2:1 aspect ratio.
+    pub fn resolution(&self) -> (i32, i32) {
+        match (self.resolution, &self.camera) {
+            (Some(resolution), _) => resolution,
+            (None, CameraKind::Equirectangular) => (IMAGE_WIDTH, IMAGE_WIDTH / 2),
+            (None, _) => (IMAGE_WIDTH, IMAGE_HEIGHT),
+        }
+    }
+
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse(env::args().skip(1))
+    }
+
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::new();
+        let mut args = args.peekable();
+        let mut filter_radius = None;
+        let mut aperture_blades = None;
+        let mut aperture_rotation = 0.;
+
+        if args.peek().map(String::as_str) == Some("denoise") {
+            args.next();
+            options.command = parse_denoise(&mut args)?;
+            return Ok(options);
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scene" => {
+                    let name: String = next_value(&mut args, &arg)?;
+                    options.scene = Scene::from_name(&name)
+                        .ok_or_else(|| format!("Unknown scene: {}", name))?;
+                }
+                "--time-limit" => {
+                    let seconds: f64 = next_value(&mut args, &arg)?;
+                    let time_limit = Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| format!("Invalid time limit: {}", seconds))?;
+                    options.time_limit = Some(time_limit);
+                }
+                "--filter" => {
+                    let name: String = next_value(&mut args, &arg)?;
+                    let kind = FilterKind::from_name(&name)
+                        .ok_or_else(|| format!("Unknown filter: {}", name))?;
+                    options.filter = Filter::from(kind, kind.default_radius());
+                }
+                "--filter-radius" => filter_radius = Some(next_value(&mut args, &arg)?),
+                "--tone-map" => {
+                    let name: String = next_value(&mut args, &arg)?;
+                    options.tone_mapper.operator = ToneMapOperator::from_name(&name)
+                        .ok_or_else(|| format!("Unknown tone map operator: {}", name))?;
+                }
+                "--exposure" => options.tone_mapper.exposure = next_value(&mut args, &arg)?,
+                "--aovs" => options.aovs = true,
+                "--denoise" => options.denoise = true,
+                "--camera" => {
+                    let name: String = next_value(&mut args, &arg)?;
+                    options.camera = CameraKind::from_name(&name)
+                        .ok_or_else(|| format!("Unknown camera: {}", name))?;
+                }
+                "--fov" => options.fov = Some(next_value(&mut args, &arg)?),
+                "--view-height" => options.view_height = next_value(&mut args, &arg)?,
+                "--orthographic" => {
+                    options.camera = CameraKind::Orthographic;
+                    options.view_height = next_value(&mut args, &arg)?;
+                }
+                "--stereo" => {
+                    let name: String = next_value(&mut args, &arg)?;
+                    options.stereo = Some(
+                        StereoLayout::from_name(&name)
+                            .ok_or_else(|| format!("Unknown stereo layout: {}", name))?,
+                    );
+                }
+                "--eye-separation" => options.eye_separation = next_value(&mut args, &arg)?,
+                "--convergence" => options.convergence_dist = Some(next_value(&mut args, &arg)?),
+                "--aperture-blades" => aperture_blades = Some(next_value(&mut args, &arg)?),
+                "--aperture-rotation" => aperture_rotation = next_value(&mut args, &arg)?,
+                "--aperture-mask" => {
+                    let path: String = next_value(&mut args, &arg)?;
+                    options.aperture = Aperture::mask(&path)?;
+                }
+                "--cat-eye" => options.cat_eye = next_value(&mut args, &arg)?,
+                "--lens-file" => {
+                    let path: String = next_value(&mut args, &arg)?;
+                    options.camera = CameraKind::LensSystem(LensElement::load(&path)?);
+                }
+                "--sensor-width" => options.sensor_width = next_value(&mut args, &arg)?,
+                "--focal-length" => options.focal_length = Some(next_value(&mut args, &arg)?),
+                "--f-stop" => options.f_stop = next_value(&mut args, &arg)?,
+                "--shutter" => options.shutter_speed = next_value(&mut args, &arg)?,
+                "--iso" => options.iso = next_value(&mut args, &arg)?,
+                "--shift" => {
+                    let horizontal = next_value(&mut args, &arg)?;
+                    let vertical = next_value(&mut args, &arg)?;
+                    options.shift = (horizontal, vertical);
+                }
+                "--tilt" => options.tilt = next_value(&mut args, &arg)?,
+                "--swing" => options.swing = next_value(&mut args, &arg)?,
+                "--spectral" => options.spectral = true,
+                "--light" => {
+                    let position = Point3::from(
+                        next_value(&mut args, &arg)?,
+                        next_value(&mut args, &arg)?,
+                        next_value(&mut args, &arg)?,
+                    );
+                    let intensity: f64 = next_value(&mut args, &arg)?;
+                    options.lights.push(PointLight::from(
+                        position,
+                        Colour::from(1., 1., 1.) * intensity,
+                    ));
+                }
+                "--hide" => {
+                    let id = next_value(&mut args, &arg)?;
+                    let name: String = next_value(&mut args, &arg)?;
+                    let kind = RayKind::from_name(&name)
+                        .ok_or_else(|| format!("Unknown ray kind: {}", name))?;
+                    options.hidden.push((id, kind));
+                }
+                "--unlink" => {
+                    let id = next_value(&mut args, &arg)?;
+                    let light = next_value(&mut args, &arg)?;
+                    if light >= 64 {
+                        return Err(format!(
+                            "Only the first 64 lights can be unlinked, not light {}",
+                            light
+                        ));
+                    }
+                    options.unlinked.push((id, light));
+                }
+                "--normal-map" => options.normal_map = Some(next_value(&mut args, &arg)?),
+                "--bump-map" => {
+                    let path = next_value(&mut args, &arg)?;
+                    let scale = next_value(&mut args, &arg)?;
+                    options.bump_map = Some((path, scale));
+                }
+                "--opacity-map" => options.opacity_map = Some(next_value(&mut args, &arg)?),
+                "--opacity-threshold" => {
+                    let threshold = next_value(&mut args, &arg)?;
+                    if !(0. ..=1.).contains(&threshold) {
+                        return Err(format!(
+                            "The opacity threshold must be between 0 and 1, not {}",
+                            threshold
+                        ));
+                    }
+                    options.opacity_threshold = Some(threshold);
+                }
+                "--frames" => {
+                    let first = next_value(&mut args, &arg)?;
+                    let last = next_value(&mut args, &arg)?;
+                    if first > last {
+                        return Err(format!(
+                            "The first frame can't come after the last, not {} to {}",
+                            first, last
+                        ));
+                    }
+                    options.frames = Some((first, last));
+                }
+                "--fps" => {
+                    let fps: f64 = next_value(&mut args, &arg)?;
+                    if fps.is_nan() || fps <= 0. {
+                        return Err(format!("The frame rate must be positive, not {}", fps));
+                    }
+                    options.fps = fps;
+                }
+                "--resolution" => {
+                    let width = next_value(&mut args, &arg)?;
+                    let height = next_value(&mut args, &arg)?;
+                    if width < 2 || height < 2 {
+                        return Err(format!("Invalid resolution: {} x {}", width, height));
+                    }
+                    options.resolution = Some((width, height));
+                }
+                "--focus-pixel" => {
+                    let i = next_value(&mut args, &arg)?;
+                    let j = next_value(&mut args, &arg)?;
+                    options.focus_pixel = Some((i, j));
+                }
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        if options.stereo.is_some() {
+            if !matches!(options.camera, CameraKind::Perspective) {
+                return Err("Stereo rendering needs the perspective camera".to_string());
+            }
+            if options.frames.is_some() {
+                return Err("Stereo rendering can't be combined with --frames".to_string());
+            }
+        }
+
+        // Animations take their camera from the scene's keyframes.
+        if options.frames.is_some() {
+            let camera_flags = [
+                (
+                    "--camera",
+                    !matches!(options.camera, CameraKind::Perspective),
+                ),
+                ("--fov", options.fov.is_some()),
+                ("--focal-length", options.focal_length.is_some()),
+                ("--focus-pixel", options.focus_pixel.is_some()),
+                ("--shift", options.shift != (0., 0.)),
+                ("--tilt", options.tilt != 0.),
+                ("--swing", options.swing != 0.),
+            ];
+            if let Some((flag, _)) = camera_flags.iter().find(|(_, set)| *set) {
+                return Err(format!("{} can't be combined with --frames", flag));
+            }
+        }
+
+        if options.opacity_threshold.is_some() && options.opacity_map.is_none() {
+            return Err("--opacity-threshold needs an --opacity-map".to_string());
+        }
+
+        // A focal length sets the field of view through the sensor size.
+        if options.fov.is_some() && options.focal_length.is_some() {
+            return Err("--fov can't be combined with --focal-length".to_string());
+        }
+
+        if options.focus_pixel.is_some() && !matches!(options.camera, CameraKind::Perspective) {
+            return Err("Auto-focus needs the perspective camera".to_string());
+        }
+
+        let physical = [
+            ("sensor width", options.sensor_width),
+            ("focal length", options.focal_length.unwrap_or(1.)),
+            ("f-stop", options.f_stop),
+            ("shutter speed", options.shutter_speed),
+            ("ISO", options.iso),
+        ];
+        for (name, value) in physical {
+            if value <= 0. {
+                return Err(format!("The {} must be positive, not {}", name, value));
+            }
+        }
+
+        if let Some(blades) = aperture_blades {
+            options.aperture = Aperture::polygon(blades, aperture_rotation)?;
+        }
+
+        if let Some(radius) = filter_radius {
+            if radius <= 0. {
+                return Err(format!(
+                    "The filter radius must be positive, not {}",
+                    radius
+                ));
+            }
+            options.filter.radius = radius;
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_denoise(args: &mut impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut paths = vec![];
+    let mut albedo = None;
+    let mut normal = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--albedo" => albedo = Some(next_value(args, &arg)?),
+            "--normal" => normal = Some(next_value(args, &arg)?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown argument: {}", arg)),
+            _ => paths.push(arg),
+        }
+    }
+
+    match paths.as_slice() {
+        [input, output] => Ok(Command::Denoise {
+            input: input.clone(),
+            output: output.clone(),
+            albedo,
+            normal,
+        }),
+        _ => Err(
+            "Usage: denoise <input.pfm> <output.pfm> [--albedo <pfm>] [--normal <pfm>]".to_string(),
+        ),
+    }
+}
+
+fn next_value<T: std::str::FromStr>(
+    args: &mut impl Iterator<Item = String>,
+    flag: &str,
+) -> Result<T, String> {
+    let value = args
+        .next()
+        .ok_or_else(|| format!("Missing value for {}", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Options, String> {
+        Options::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parses_flags_and_values() {
+        let options =
+            parse("--time-limit 2.5 --fov 30 --spectral --hide 4 shadow --unlink 2 1").unwrap();
+        assert_eq!(options.time_limit, Some(Duration::from_millis(2500)));
+        assert_eq!(options.fov, Some(30.));
+        assert!(options.spectral);
+        assert_eq!(options.hidden, vec![(4, RayKind::Shadow)]);
+        assert_eq!(options.unlinked, vec![(2, 1)]);
+
+        let options =
+            parse("--bump-map tiles.pfm 0.02 --opacity-map leaf.ppm --opacity-threshold 0.5")
+                .unwrap();
+        assert_eq!(options.bump_map, Some(("tiles.pfm".to_string(), 0.02)));
+        assert_eq!(options.opacity_map.as_deref(), Some("leaf.ppm"));
+        assert_eq!(options.opacity_threshold, Some(0.5));
+    }
+
+    #[test]
+    fn defaults_equirectangular_images_to_two_to_one() {
+        let equirectangular = parse("--camera equirectangular").unwrap();
+        assert_eq!(equirectangular.resolution(), (IMAGE_WIDTH, IMAGE_WIDTH / 2));
+        let custom = parse("--camera equirectangular --resolution 64 48").unwrap();
+        assert_eq!(custom.resolution(), (64, 48));
+        assert_eq!(parse("").unwrap().resolution(), (IMAGE_WIDTH, IMAGE_HEIGHT));
+    }
+
+    #[test]
+    fn keeps_orthographic_flag_as_an_alias() {
+        let options = parse("--orthographic 6").unwrap();
+        assert!(matches!(options.camera, CameraKind::Orthographic));
+        assert_eq!(options.view_height, 6.);
+    }
+
+    #[test]
+    fn reports_invalid_options() {
+        let error = |args| parse(args).err().unwrap();
+        assert_eq!(error("--time-limit -1"), "Invalid time limit: -1");
+        assert_eq!(error("--fov wide"), "Invalid value for --fov: wide");
+        assert_eq!(error("--shift 0.1"), "Missing value for --shift");
+        assert_eq!(error("--camera pinhole"), "Unknown camera: pinhole");
+        assert_eq!(error("--scene cornell"), "Unknown scene: cornell");
+        assert_eq!(
+            error("--aperture-blades 2"),
+            "An aperture needs at least 3 blades, not 2"
+        );
+        assert_eq!(
+            error("--filter-radius 0"),
+            "The filter radius must be positive, not 0"
+        );
+        assert_eq!(error("--resolution 0 10"), "Invalid resolution: 0 x 10");
+        assert!(error("--aperture-mask missing.ppm").starts_with("Unable to open missing.ppm"));
+        assert!(
+            error("--lens-file missing.dat").starts_with("Unable to read lens file missing.dat")
+        );
+        assert_eq!(
+            error("--camera orthographic --stereo two-files"),
+            "Stereo rendering needs the perspective camera"
+        );
+        assert_eq!(
+            error("--stereo side-by-side --frames 0 2"),
+            "Stereo rendering can't be combined with --frames"
+        );
+        assert_eq!(
+            error("--frames 0 2 --fov 30"),
+            "--fov can't be combined with --frames"
+        );
+        assert_eq!(
+            error("--camera cube-map --frames 0 2"),
+            "--camera can't be combined with --frames"
+        );
+        assert_eq!(
+            error("--frames 0 2 --shift 0.1 0"),
+            "--shift can't be combined with --frames"
+        );
+        assert_eq!(
+            error("--frames 3 1"),
+            "The first frame can't come after the last, not 3 to 1"
+        );
+        assert_eq!(error("--fps 0"), "The frame rate must be positive, not 0");
+        assert_eq!(
+            error("--focal-length 50 --f-stop 0"),
+            "The f-stop must be positive, not 0"
+        );
+        assert_eq!(
+            error("--fov 30 --focal-length 50"),
+            "--fov can't be combined with --focal-length"
+        );
+        assert_eq!(error("--iso -100"), "The ISO must be positive, not -100");
+        assert_eq!(
+            error("--camera cube-map --focus-pixel 3 4"),
+            "Auto-focus needs the perspective camera"
+        );
+        assert_eq!(error("--hide 3 glossy"), "Unknown ray kind: glossy");
+        assert_eq!(
+            error("--unlink 3 64"),
+            "Only the first 64 lights can be unlinked, not light 64"
+        );
+        assert_eq!(
+            error("--opacity-map leaf.ppm --opacity-threshold 2"),
+            "The opacity threshold must be between 0 and 1, not 2"
+        );
+        assert_eq!(
+            error("--opacity-threshold 0.5"),
+            "--opacity-threshold needs an --opacity-map"
+        );
+        assert_eq!(error("--verbose"), "Unknown argument: --verbose");
+        assert!(error("denoise in.pfm").starts_with("Usage: denoise"));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    utils::random_f64,
+    vec3::{dot, Point3, Vec3},
+};
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise with values in about [-1, 1], as in "Ray Tracing: The Next Week".
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new() -> Self {
+        Self {
+            gradients: (0..POINT_COUNT)
+                .map(|_| Vec3::random(-1., 1.).unit_vector())
+                .collect(),
+            perm_x: Self::generate_perm(),
+            perm_y: Self::generate_perm(),
+            perm_z: Self::generate_perm(),
+        }
+    }
+
+    fn generate_perm() -> Vec<usize> {
+        let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+        for i in (1..POINT_COUNT).rev() {
+            let target = random_f64(0., (i + 1) as f64) as usize;
+            perm.swap(i, target);
+        }
+        perm
+    }
+
+    pub fn noise(&self, p: Point3) -> f64 {
+        let floor = [p.x().floor(), p.y().floor(), p.z().floor()];
+        let frac = [p.x() - floor[0], p.y() - floor[1], p.z() - floor[2]];
+        // Hermite smoothing of the interpolation weights.
+        let smooth = frac.map(|f| f * f * (3. - 2. * f));
+
+        let mut accum = 0.;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = self.perm_x[(floor[0] as i64 + di) as usize & 255]
+                        ^ self.perm_y[(floor[1] as i64 + dj) as usize & 255]
+                        ^ self.perm_z[(floor[2] as i64 + dk) as usize & 255];
+                    let weight = Vec3::from(
+                        frac[0] - di as f64,
+                        frac[1] - dj as f64,
+                        frac[2] - dk as f64,
+                    );
+                    let (fi, fj, fk) = (di as f64, dj as f64, dk as f64);
+                    accum += (fi * smooth[0] + (1. - fi) * (1. - smooth[0]))
+                        * (fj * smooth[1] + (1. - fj) * (1. - smooth[1]))
+                        * (fk * smooth[2] + (1. - fk) * (1. - smooth[2]))
+                        * dot(self.gradients[index], weight);
+                }
+            }
+        }
+        accum
+    }
+
+    // Sum of `depth` octaves of noise, each at twice the frequency and half the weight of the last.
+    pub fn turbulence(&self, p: Point3, depth: i32) -> f64 {
+        let mut accum = 0.;
+        let mut temp_p = p;
+        let mut weight = 1.;
+
+        for _ in 0..depth {
+            accum += weight * self.noise(temp_p);
+            weight *= 0.5;
+            temp_p *= 2.;
+        }
+
+        accum.abs()
+    }
+}
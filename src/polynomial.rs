@@ -0,0 +1,160 @@
+// Polynomials are given by their coefficients in order of increasing power.
+
+pub fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0., |sum, &c| sum * x + c)
+}
+
+pub fn derivative(coefficients: &[f64]) -> Vec<f64> {
+    coefficients
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(power, &c)| c * power as f64)
+        .collect()
+}
+
+// Real roots in [lo, hi], in increasing order. The roots of the derivative split the range into
+// pieces on which the polynomial is monotonic, so each piece holds at most one root, which is
+// bracketed and then found by Newton's method falling back to bisection. This stays accurate
+// where closed-form quartic solutions lose precision, at the cost of missing roots of even
+// multiplicity that the derivative's roots don't land on exactly.
+pub fn roots_in(coefficients: &[f64], lo: f64, hi: f64) -> Vec<f64> {
+    let scale = coefficients.iter().fold(0., |m: f64, c| m.max(c.abs()));
+    let degree = match coefficients.iter().rposition(|c| c.abs() > scale * 1e-14) {
+        Some(degree) => degree,
+        None => return vec![],
+    };
+    let coefficients = &coefficients[..=degree];
+
+    if degree == 0 {
+        return vec![];
+    }
+    if degree == 1 {
+        let root = -coefficients[0] / coefficients[1];
+        return if lo <= root && root <= hi {
+            vec![root]
+        } else {
+            vec![]
+        };
+    }
+
+    let mut ends = vec![lo];
+    ends.extend(roots_in(&derivative(coefficients), lo, hi));
+    ends.push(hi);
+
+    let mut roots: Vec<f64> = vec![];
+    for pair in ends.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let (fa, fb) = (evaluate(coefficients, a), evaluate(coefficients, b));
+        let root = if fa == 0. {
+            Some(a)
+        } else if fa.signum() != fb.signum() {
+            Some(if fb == 0. {
+                b
+            } else {
+                refine(coefficients, a, b, fa)
+            })
+        } else {
+            None
+        };
+        if let Some(root) = root {
+            if roots.last().is_none_or(|&last| last < root) {
+                roots.push(root);
+            }
+        }
+    }
+    roots
+}
+
+// Root within the bracket [a, b], where the polynomial has the sign of `fa` at `a` and the
+// opposite sign at `b`.
+fn refine(coefficients: &[f64], mut a: f64, mut b: f64, fa: f64) -> f64 {
+    let slope = derivative(coefficients);
+    let mut x = 0.5 * (a + b);
+
+    for _ in 0..100 {
+        let f = evaluate(coefficients, x);
+        if f == 0. {
+            return x;
+        }
+        if f.signum() == fa.signum() {
+            a = x;
+        } else {
+            b = x;
+        }
+
+        let df = evaluate(&slope, x);
+        let newton = x - f / df;
+        let next = if df != 0. && a < newton && newton < b {
+            newton
+        } else {
+            0.5 * (a + b)
+        };
+
+        if (next - x).abs() <= 1e-15 * (1. + x.abs()) || b - a <= 1e-15 * (1. + x.abs()) {
+            return next;
+        }
+        x = next;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Coefficients of the product of (x - root) over the roots.
+    fn from_roots(roots: &[f64]) -> Vec<f64> {
+        roots.iter().fold(vec![1.], |p, &root| {
+            let mut product = vec![0.; p.len() + 1];
+            for (i, &c) in p.iter().enumerate() {
+                product[i] -= root * c;
+                product[i + 1] += c;
+            }
+            product
+        })
+    }
+
+    fn assert_roots(found: &[f64], expected: &[f64], tolerance: f64) {
+        assert_eq!(found.len(), expected.len(), "found {:?}", found);
+        for (f, e) in found.iter().zip(expected) {
+            assert!((f - e).abs() <= tolerance, "found {:?}", found);
+        }
+    }
+
+    #[test]
+    fn finds_simple_roots_in_order() {
+        let p = from_roots(&[3., -1., 2., 0.5]);
+        assert_roots(&roots_in(&p, -10., 10.), &[-1., 0.5, 2., 3.], 1e-12);
+    }
+
+    #[test]
+    fn only_reports_roots_in_range() {
+        let p = from_roots(&[3., -1., 2., 0.5]);
+        assert_roots(&roots_in(&p, 0., 2.5), &[0.5, 2.], 1e-12);
+    }
+
+    #[test]
+    fn separates_close_roots() {
+        let p = from_roots(&[1., 1. + 1e-6, 5., 7.]);
+        assert_roots(&roots_in(&p, 0., 10.), &[1., 1. + 1e-6, 5., 7.], 1e-9);
+    }
+
+    #[test]
+    fn handles_roots_of_very_different_size() {
+        let p = from_roots(&[1e-3, 2., 1e3, 2e3]);
+        assert_roots(&roots_in(&p, 0., 1e4), &[1e-3, 2., 1e3, 2e3], 1e-9);
+    }
+
+    #[test]
+    fn handles_missing_roots_and_lower_degree() {
+        // x^4 + 1 has no real roots.
+        assert!(roots_in(&[1., 0., 0., 0., 1.], -10., 10.).is_empty());
+        // A vanishing leading coefficient leaves a quadratic.
+        assert_roots(
+            &roots_in(&[-4., 0., 1., 0., 0.], -10., 10.),
+            &[-2., 2.],
+            1e-12,
+        );
+    }
+}
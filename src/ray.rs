@@ -1,9 +1,36 @@
 use crate::vec3::{Point3, Vec3};
 
+// What a ray is being traced for, so objects can choose which rays see them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RayKind {
+    Camera,
+    Shadow,
+    Reflection,
+    Refraction,
+    Diffuse,
+}
+
+impl RayKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "camera" => Some(Self::Camera),
+            "shadow" => Some(Self::Shadow),
+            "reflection" => Some(Self::Reflection),
+            "refraction" => Some(Self::Refraction),
+            "diffuse" => Some(Self::Diffuse),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct Ray {
     pub orig: Point3,
     pub dir: Vec3,
+    pub kind: RayKind,
+    // The wavelengths in nanometres whose radiance the ray carries in spectral mode, or `None`
+    // when tracing RGB.
+    pub wavelengths: Option<Vec3>,
 }
 
 impl Ray {
@@ -11,15 +38,24 @@ impl Ray {
         Self {
             orig: Point3::new(),
             dir: Vec3::new(),
+            kind: RayKind::Camera,
+            wavelengths: None,
         }
     }
     pub fn from(origin: Point3, direction: Vec3) -> Self {
         Self {
             orig: origin,
             dir: direction,
+            kind: RayKind::Camera,
+            wavelengths: None,
         }
     }
 
+    pub fn with_kind(mut self, kind: RayKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
     pub fn origin(&self) -> Point3 {
         self.orig
     }
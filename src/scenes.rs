@@ -0,0 +1,324 @@
+use crate::{
+    material::{
+        conductor::Conductor,
+        cutout::{Cutout, Opacity, OpacityMode},
+        layered::Layered,
+        material::{Dielectric, Lambertian, Material, Metal},
+        microfacet::AnisotropicMetal,
+        mix::Mix,
+        subsurface::Subsurface,
+        thin_film::ThinFilm,
+    },
+    objects::{
+        csg::Csg,
+        hittable::Hittable,
+        hittable_list::HittableList,
+        plane::Plane,
+        quadric::{Cone, Cylinder, Hyperboloid, Paraboloid},
+        sdf::{Sdf, SdfObject},
+        sphere::Sphere,
+        torus::Torus,
+    },
+    perlin::Perlin,
+    utils::random_f64,
+    vec3::{Colour, Point3, Vec3},
+};
+use std::rc::Rc;
+
+// The scenes that can be rendered. All are framed for a camera at (13, 2, 3) looking at the
+// origin.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Scene {
+    Random,
+    // Rows of spheres showing off the materials.
+    Materials,
+    // The geometric primitives and solids built from them.
+    Shapes,
+}
+
+impl Scene {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(Self::Random),
+            "materials" => Some(Self::Materials),
+            "shapes" => Some(Self::Shapes),
+            _ => None,
+        }
+    }
+
+    // Builds the scene standing on a ground plane of the given material.
+    pub fn build(&self, ground: Rc<dyn Material>) -> Result<HittableList, String> {
+        let mut world = HittableList::new();
+        world.add(Rc::new(Plane::from(
+            Point3::new(),
+            Vec3::from(0., 1., 0.),
+            ground,
+        )));
+        match self {
+            Self::Random => random_scene(&mut world),
+            Self::Materials => materials_scene(&mut world),
+            Self::Shapes => shapes_scene(&mut world)?,
+        }
+        Ok(world)
+    }
+}
+
+fn random_scene(world: &mut HittableList) {
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat = random_f64(0., 1.);
+            let centre = Point3::from(
+                a as f64 + 0.9 * random_f64(0., 1.),
+                0.2,
+                b as f64 + 0.9 * random_f64(0., 1.),
+            );
+
+            if (centre - Point3::from(4., 0.2, 0.)).length() > 0.9 {
+                let sphere_material: Rc<dyn Material>;
+
+                if choose_mat < 0.8 {
+                    let albedo = Colour::random(0., 1.) * Colour::random(0., 1.);
+                    sphere_material = Rc::new(Lambertian::from(albedo));
+                    world.add(Rc::new(Sphere::from(centre, 0.2, sphere_material)));
+                } else if choose_mat < 0.95 {
+                    let albedo = Colour::random(0.5, 1.);
+                    let fuzz = random_f64(0., 0.5);
+                    sphere_material = Rc::new(Metal::from(albedo, fuzz));
+                    world.add(Rc::new(Sphere::from(centre, 0.2, sphere_material)));
+                } else {
+                    sphere_material = Rc::new(Dielectric::from(1.5));
+                    world.add(Rc::new(Sphere::from(centre, 0.2, sphere_material)));
+                }
+            }
+        }
+    }
+
+    let material1 = Rc::new(Dielectric::from(1.5));
+    world.add(Rc::new(Sphere::from(
+        Point3::from(0., 1., 0.),
+        1.0,
+        material1,
+    )));
+
+    let material2 = Rc::new(Lambertian::from(Colour::from(0.4, 0.2, 0.1)));
+    world.add(Rc::new(Sphere::from(
+        Point3::from(-4., 1., 0.),
+        1.0,
+        material2,
+    )));
+
+    let material3 = Rc::new(Metal::from(Colour::from(0.7, 0.6, 0.5), 0.));
+    world.add(Rc::new(Sphere::from(
+        Point3::from(4., 1., 0.),
+        1.0,
+        material3,
+    )));
+}
+
+// Centre of the `i`th sphere of radius 0.4 on the ground, filling rows across the view from the
+// front. Each row is shifted to show through the gaps in the one in front of it.
+fn slot(i: usize) -> Point3 {
+    let (row, column) = ((i / 6) as f64, (i % 6) as f64);
+    Point3::from(2. - 2.5 * row, 0.4, -2.25 + 0.9 * column - 0.1 * row)
+}
+
+fn materials_scene(world: &mut HittableList) {
+    let perlin = Rc::new(Perlin::new());
+    let noise = perlin.clone();
+    let materials: Vec<Rc<dyn Material>> = vec![
+        // Dispersive glasses, whose colour fringes only appear with --spectral.
+        Rc::new(Dielectric::bk7()),
+        Rc::new(Dielectric::sf11()),
+        Rc::new(Dielectric::cauchy(1.67, 0.0743)),
+        // Measured metals.
+        Rc::new(Conductor::from_name("gold", 0.).unwrap()),
+        Rc::new(Conductor::from_name("copper", 0.05).unwrap()),
+        Rc::new(Conductor::from_name("aluminium", 0.2).unwrap()),
+        // Thin films: oil on a dark surface, varying in thickness with noise, and anodised
+        // titanium.
+        Rc::new(ThinFilm::from(
+            Rc::new(Lambertian::from(Colour::from(0.02, 0.02, 0.02))),
+            Rc::new(move |p| 250. + 400. * noise.turbulence(p * 4., 5)),
+            1.45,
+            (1.5, 0.),
+        )),
+        Rc::new(ThinFilm::uniform(
+            Rc::new(Metal::from(Colour::from(0.6, 0.6, 0.6), 0.)),
+            250.,
+            2.4,
+            (2.7, 3.8),
+        )),
+        // Wax, lit through by light scattering beneath its surface.
+        Rc::new(Subsurface::from(
+            Colour::from(0.95, 0.9, 0.8),
+            Colour::from(0.3, 0.15, 0.08),
+            1.4,
+        )),
+        // Red paint under a clear coat, and steel patched with rust.
+        Rc::new(Layered::from(
+            Rc::new(Metal::from(Colour::from(1., 1., 1.), 0.)),
+            Rc::new(Lambertian::from(Colour::from(0.6, 0.05, 0.05))),
+            1.5,
+        )),
+        Rc::new(Mix::from(
+            Rc::new(Metal::from(Colour::from(0.6, 0.6, 0.6), 0.1)),
+            Rc::new(Lambertian::from(Colour::from(0.4, 0.15, 0.05))),
+            Rc::new(move |p| 2. * perlin.turbulence(p * 3., 7)),
+        )),
+        // Metal brushed around the sphere.
+        Rc::new(AnisotropicMetal::from(
+            Colour::from(0.9, 0.9, 0.9),
+            0.05,
+            0.4,
+            0.,
+        )),
+        // Satin, a constant blend of diffuse and glossy.
+        Rc::new(Mix::constant(
+            Rc::new(Lambertian::from(Colour::from(0.3, 0.1, 0.4))),
+            Rc::new(Metal::from(Colour::from(0.8, 0.7, 0.9), 0.3)),
+            0.3,
+        )),
+        // A half-there ghost.
+        Rc::new(Cutout::from(
+            Rc::new(Lambertian::from(Colour::from(0.9, 0.9, 0.9))),
+            Opacity::Constant(0.5),
+            OpacityMode::Stochastic,
+        )),
+    ];
+
+    for (i, material) in materials.into_iter().enumerate() {
+        world.add(Rc::new(Sphere::from(slot(i), 0.4, material)));
+    }
+}
+
+// An axis-aligned box, as the intersection of the half-spaces behind its six faces.
+fn cuboid(
+    centre: Point3,
+    half_extents: Vec3,
+    m: Rc<dyn Material>,
+) -> Result<Rc<dyn Hittable>, String> {
+    let mut solid: Option<Rc<dyn Hittable>> = None;
+    for axis in 0..3 {
+        for side in [-1., 1.] {
+            let mut normal = Vec3::new();
+            normal.e[axis] = side;
+            let point = centre + normal * half_extents[axis];
+            let face: Rc<dyn Hittable> = Rc::new(Plane::from(point, normal, m.clone()));
+            solid = Some(match solid {
+                Some(solid) => Rc::new(Csg::intersection(solid, face)?),
+                None => face,
+            });
+        }
+    }
+    Ok(solid.unwrap())
+}
+
+fn shapes_scene(world: &mut HittableList) -> Result<(), String> {
+    let red = Rc::new(Lambertian::from(Colour::from(0.7, 0.15, 0.1)));
+    let white = Rc::new(Lambertian::from(Colour::from(0.8, 0.8, 0.8)));
+    let glass = Rc::new(Dielectric::from(1.5));
+
+    // Constructive solid geometry: a sphere with a bite out of it, a lens where two spheres
+    // overlap and a die, the part of a box inside a sphere.
+    let centre = slot(0);
+    world.add(Rc::new(Csg::difference(
+        Rc::new(Sphere::from(centre, 0.4, red.clone())),
+        Rc::new(Sphere::from(
+            centre + Vec3::from(0.2, 0.2, 0.2),
+            0.3,
+            red.clone(),
+        )),
+    )?));
+    let centre = slot(1);
+    let offset = Vec3::from(0.7, 0., 0.);
+    world.add(Rc::new(Csg::intersection(
+        Rc::new(Sphere::from(centre + offset, 0.8, glass.clone())),
+        Rc::new(Sphere::from(centre - offset, 0.8, glass)),
+    )?));
+    let centre = slot(2);
+    world.add(Rc::new(Csg::intersection(
+        cuboid(centre, Vec3::from(0.33, 0.33, 0.33), white.clone())?,
+        Rc::new(Sphere::from(centre, 0.43, white)),
+    )?));
+
+    // Quadrics stand on the ground below their slots. The hyperboloid is only swept part of the
+    // way round, to show its inside.
+    let base = |i| slot(i) - Vec3::from(0., 0.4, 0.);
+    let blue = Rc::new(Lambertian::from(Colour::from(0.1, 0.2, 0.6)));
+    let metal = Rc::new(Metal::from(Colour::from(0.8, 0.8, 0.8), 0.1));
+    world.add(Rc::new(Cylinder::from(
+        base(3),
+        0.3,
+        (0., 0.8),
+        360.,
+        true,
+        blue.clone(),
+    )));
+    world.add(Rc::new(Cone::from(base(4), 0.35, 0.8, 360., blue)));
+    world.add(Rc::new(Paraboloid::from(
+        base(5),
+        0.4,
+        0.8,
+        360.,
+        metal.clone(),
+    )));
+    world.add(Rc::new(Hyperboloid::from(
+        base(6) + Vec3::from(0., 0.4, 0.),
+        0.2,
+        0.35,
+        0.4,
+        270.,
+        metal,
+    )));
+
+    // Distance fields: two spheres melted together and a twisted bar with rounded edges.
+    let green = Rc::new(Lambertian::from(Colour::from(0.2, 0.5, 0.2)));
+    let blob = Sdf::sphere(0.25)
+        .translate(Vec3::from(0., -0.15, -0.15))
+        .smooth_union(Sdf::sphere(0.2).translate(Vec3::from(0., 0.15, 0.15)), 0.2);
+    world.add(Rc::new(SdfObject::from(
+        blob.translate(slot(7)),
+        green.clone(),
+    )));
+    let bar = Sdf::rounded_box(Vec3::from(0.15, 0.4, 0.15), 0.04).twist(2.);
+    world.add(Rc::new(SdfObject::from(bar.translate(slot(8)), green)));
+
+    // A capsule, joining a capped cylinder and two spheres.
+    let centre = slot(10);
+    let end = Vec3::from(0., 0.2, 0.);
+    let capsule = Csg::union(
+        Rc::new(Cylinder::from(
+            centre,
+            0.2,
+            (-0.2, 0.2),
+            360.,
+            true,
+            red.clone(),
+        )),
+        Rc::new(Csg::union(
+            Rc::new(Sphere::from(centre + end, 0.2, red.clone())),
+            Rc::new(Sphere::from(centre - end, 0.2, red)),
+        )?),
+    )?;
+    world.add(Rc::new(capsule));
+
+    // A ring lying on the ground, and a lattice of cubes hovering over a ring traced as a
+    // distance field.
+    let gold = Rc::new(Metal::from(Colour::from(0.8, 0.6, 0.2), 0.05));
+    world.add(Rc::new(Torus::from(
+        base(9) + Vec3::from(0., 0.1, 0.),
+        0.3,
+        0.1,
+        gold.clone(),
+    )));
+    let lattice = Sdf::torus(0.3, 0.06)
+        .translate(Vec3::from(0., -0.34, 0.))
+        .union(
+            Sdf::cuboid(Vec3::from(0.05, 0.05, 0.05))
+                .repeat(Vec3::from(0.2, 0.2, 0.2), Vec3::from(1., 1., 1.))
+                .translate(Vec3::from(0., 0.1, 0.)),
+        );
+    world.add(Rc::new(SdfObject::from(lattice.translate(slot(11)), gold)));
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::{
+    utils::random_f64,
+    vec3::{Colour, Vec3},
+};
+use std::sync::OnceLock;
+
+// Visible range sampled in spectral mode, in nanometres.
+pub const LAMBDA_MIN: f64 = 380.;
+pub const LAMBDA_MAX: f64 = 720.;
+
+// Representative wavelengths of the red, green and blue channels, used to evaluate
+// wavelength-dependent materials when tracing RGB.
+pub const RGB_WAVELENGTHS: [f64; 3] = [610., 550., 465.];
+
+// Picks a hero wavelength uniformly and spaces two more evenly after it, wrapping around the
+// visible range. Each path carries radiance for all three in the components of a `Vec3`.
+pub fn sample_wavelengths() -> Vec3 {
+    let range = LAMBDA_MAX - LAMBDA_MIN;
+    let hero = random_f64(0., range);
+    let wavelength = |k: f64| LAMBDA_MIN + (hero + k * range / 3.) % range;
+    Vec3::from(wavelength(0.), wavelength(1.), wavelength(2.))
+}
+
+// Value of a smooth spectrum at `lambda` built from an RGB colour. The three basis functions sum
+// to one everywhere, so white becomes a flat spectrum and colours within [0, 1] stay within
+// [0, 1], which keeps upsampled albedos energy conserving.
+pub fn rgb_to_spectrum(colour: Colour, lambda: f64) -> f64 {
+    let blue = 1. - smoothstep(440., 530., lambda);
+    let red = smoothstep(560., 630., lambda);
+    let green = 1. - blue - red;
+    colour.x() * red + colour.y() * green + colour.z() * blue
+}
+
+// Converts an RGB colour to its values at the ray's wavelengths, or leaves it alone when the ray
+// isn't spectral.
+pub fn upsample(colour: Colour, wavelengths: Option<Vec3>) -> Colour {
+    match wavelengths {
+        Some(lambda) => Colour::from(
+            rgb_to_spectrum(colour, lambda.x()),
+            rgb_to_spectrum(colour, lambda.y()),
+            rgb_to_spectrum(colour, lambda.z()),
+        ),
+        None => colour,
+    }
+}
+
+// The wavelengths at which to evaluate a wavelength-dependent material for a ray.
+pub fn shading_wavelengths(wavelengths: Option<Vec3>) -> Vec3 {
+    wavelengths
+        .unwrap_or_else(|| Vec3::from(RGB_WAVELENGTHS[0], RGB_WAVELENGTHS[1], RGB_WAVELENGTHS[2]))
+}
+
+// The CIE 1931 colour matching functions, using the multi-lobe fit from Wyman, Sloan and Shirley,
+// "Simple Analytic Approximations to the CIE XYZ Color Matching Functions" (2013).
+pub fn cie_xyz(lambda: f64) -> Vec3 {
+    let g = |mu: f64, sigma_below: f64, sigma_above: f64| {
+        let sigma = if lambda < mu {
+            sigma_below
+        } else {
+            sigma_above
+        };
+        let t = (lambda - mu) / sigma;
+        (-0.5 * t * t).exp()
+    };
+
+    Vec3::from(
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    )
+}
+
+// Linear sRGB from CIE XYZ, for a D65 white point.
+pub fn xyz_to_rgb(xyz: Vec3) -> Colour {
+    Colour::from(
+        3.2406 * xyz.x() - 1.5372 * xyz.y() - 0.4986 * xyz.z(),
+        -0.9689 * xyz.x() + 1.8758 * xyz.y() + 0.0415 * xyz.z(),
+        0.0557 * xyz.x() - 0.2040 * xyz.y() + 1.0570 * xyz.z(),
+    )
+}
+
+// Linear sRGB of a flat unit spectrum, which the film divides out so that white surfaces under
+// white light come out white.
+fn white_point() -> Colour {
+    static WHITE: OnceLock<Colour> = OnceLock::new();
+    *WHITE.get_or_init(|| {
+        let steps = (LAMBDA_MAX - LAMBDA_MIN) as i32;
+        let xyz = (0..steps).fold(Vec3::new(), |sum, i| {
+            sum + cie_xyz(LAMBDA_MIN + i as f64 + 0.5)
+        });
+        xyz_to_rgb(xyz)
+    })
+}
+
+// Converts radiance carried at `wavelengths` by one path into a white-balanced linear sRGB
+// estimate.
+pub fn spectrum_to_rgb(radiance: Vec3, wavelengths: Vec3) -> Colour {
+    // Each wavelength was sampled with density 1 / (LAMBDA_MAX - LAMBDA_MIN).
+    let scale = (LAMBDA_MAX - LAMBDA_MIN) / 3.;
+    let xyz = (cie_xyz(wavelengths.x()) * radiance.x()
+        + cie_xyz(wavelengths.y()) * radiance.y()
+        + cie_xyz(wavelengths.z()) * radiance.z())
+        * scale;
+    let rgb = xyz_to_rgb(xyz);
+    let white = white_point();
+    Colour::from(
+        rgb.x() / white.x(),
+        rgb.y() / white.y(),
+        rgb.z() / white.z(),
+    )
+}
+
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_spectrum_is_white() {
+        // Average the estimate over hero wavelengths spread evenly across the range.
+        let steps = 3400;
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let sum = (0..steps).fold(Colour::new(), |sum, i| {
+            let hero = (i as f64 + 0.5) * range / (3 * steps) as f64;
+            let wavelength = |k: f64| LAMBDA_MIN + hero + k * range / 3.;
+            let wavelengths = Vec3::from(wavelength(0.), wavelength(1.), wavelength(2.));
+            sum + spectrum_to_rgb(Vec3::from(1., 1., 1.), wavelengths)
+        });
+        let average = sum / steps as f64;
+        for c in 0..3 {
+            assert!((average[c] - 1.).abs() < 1e-3, "white is {:?}", average);
+        }
+    }
+
+    #[test]
+    fn upsampled_white_is_flat() {
+        for lambda in [400., 480., 550., 600., 700.] {
+            let value = rgb_to_spectrum(Colour::from(1., 1., 1.), lambda);
+            assert!((value - 1.).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::vec3::Colour;
+
+#[derive(Copy, Clone, Debug)]
+pub enum ToneMapOperator {
+    // Leaves values untouched, so anything above 1 is clipped when quantised.
+    Clamp,
+    Reinhard,
+    ExtendedReinhard { white: f64 },
+    Aces,
+    Hable,
+}
+
+impl ToneMapOperator {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "clamp" => Some(Self::Clamp),
+            "reinhard" => Some(Self::Reinhard),
+            "extended-reinhard" => Some(Self::ExtendedReinhard { white: 4. }),
+            "aces" => Some(Self::Aces),
+            "hable" => Some(Self::Hable),
+            _ => None,
+        }
+    }
+}
+
+// Maps linear HDR radiance into [0, 1] before gamma correction. `exposure` is in stops (EV), so
+// each unit doubles or halves the brightness of the image.
+#[derive(Copy, Clone, Debug)]
+pub struct ToneMapper {
+    pub operator: ToneMapOperator,
+    pub exposure: f64,
+}
+
+impl ToneMapper {
+    pub fn from(operator: ToneMapOperator, exposure: f64) -> Self {
+        Self { operator, exposure }
+    }
+
+    pub fn apply(&self, colour: Colour) -> Colour {
+        let colour = colour * 2f64.powf(self.exposure);
+
+        match self.operator {
+            ToneMapOperator::Clamp => colour,
+            ToneMapOperator::Reinhard => scale_luminance(colour, |l| l / (1. + l)),
+            ToneMapOperator::ExtendedReinhard { white } => {
+                scale_luminance(colour, |l| l * (1. + l / (white * white)) / (1. + l))
+            }
+            ToneMapOperator::Aces => map_channels(colour, aces),
+            ToneMapOperator::Hable => {
+                let white_scale = 1. / hable(HABLE_WHITE);
+                map_channels(colour * HABLE_EXPOSURE_BIAS, |x| hable(x) * white_scale)
+            }
+        }
+    }
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        Self::from(ToneMapOperator::Clamp, 0.)
+    }
+}
+
+pub fn luminance(colour: Colour) -> f64 {
+    0.2126 * colour.x() + 0.7152 * colour.y() + 0.0722 * colour.z()
+}
+
+fn scale_luminance(colour: Colour, f: impl Fn(f64) -> f64) -> Colour {
+    let l = luminance(colour);
+    if l <= 0. {
+        return Colour::new();
+    }
+    colour * (f(l) / l)
+}
+
+fn map_channels(colour: Colour, f: impl Fn(f64) -> f64) -> Colour {
+    Colour::from(f(colour.x()), f(colour.y()), f(colour.z()))
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve, which overshoots 1 for bright values and
+// so is saturated like the original.
+fn aces(x: f64) -> f64 {
+    let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+    ((x * (a * x + b)) / (x * (c * x + d) + e)).clamp(0., 1.)
+}
+
+const HABLE_EXPOSURE_BIAS: f64 = 2.;
+const HABLE_WHITE: f64 = 11.2;
+
+// John Hable's filmic curve from Uncharted 2.
+fn hable(x: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.5, 0.1, 0.2, 0.02, 0.3);
+    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colour::rescale_colour;
+
+    fn operators() -> Vec<ToneMapOperator> {
+        ["clamp", "reinhard", "extended-reinhard", "aces", "hable"]
+            .iter()
+            .map(|name| ToneMapOperator::from_name(name).unwrap())
+            .collect()
+    }
+
+    fn grey(value: f64) -> Colour {
+        Colour::from(value, value, value)
+    }
+
+    #[test]
+    fn maps_black_to_black_and_is_monotonic() {
+        for operator in operators() {
+            let tone_mapper = ToneMapper::from(operator, 0.);
+            assert_eq!(
+                tone_mapper.apply(Colour::new()).length(),
+                0.,
+                "{:?}",
+                operator
+            );
+
+            let mut previous = 0.;
+            for i in 1..200 {
+                let mapped = luminance(tone_mapper.apply(grey(i as f64 * 0.1)));
+                assert!(mapped >= previous, "{:?}", operator);
+                previous = mapped;
+            }
+        }
+    }
+
+    // Reinhard compresses luminance, so only its luminance is bounded for saturated colours.
+    #[test]
+    fn reinhard_and_aces_stay_in_range() {
+        let reinhard = ToneMapper::from(ToneMapOperator::Reinhard, 0.);
+        let aces = ToneMapper::from(ToneMapOperator::Aces, 0.);
+        for value in [0.01, 0.5, 1., 10., 1e3, 1e6] {
+            let colour = Colour::from(value, value * 0.5, value * 0.1);
+            assert!((0. ..=1.).contains(&luminance(reinhard.apply(colour))));
+            let mapped = aces.apply(colour);
+            for channel in [mapped.x(), mapped.y(), mapped.z()] {
+                assert!((0. ..=1.).contains(&channel));
+            }
+        }
+    }
+
+    #[test]
+    fn exposure_scales_by_stops() {
+        let brighter = ToneMapper::from(ToneMapOperator::Clamp, 2.);
+        assert!((brighter.apply(grey(0.1)) - grey(0.4)).length() < 1e-12);
+        let darker = ToneMapper::from(ToneMapOperator::Clamp, -1.);
+        assert!((darker.apply(grey(0.1)) - grey(0.05)).length() < 1e-12);
+
+        let reinhard = ToneMapper::from(ToneMapOperator::Reinhard, 1.);
+        let expected = ToneMapper::from(ToneMapOperator::Reinhard, 0.).apply(grey(0.6));
+        assert!((reinhard.apply(grey(0.3)) - expected).length() < 1e-12);
+    }
+
+    #[test]
+    fn transfer_maps_black_and_white_to_the_ends_of_the_range() {
+        let tone_mapper = ToneMapper::default();
+        assert_eq!(rescale_colour(Colour::new(), 1, &tone_mapper), (0, 0, 0));
+        assert_eq!(rescale_colour(grey(1.), 1, &tone_mapper), (255, 255, 255));
+        assert_eq!(rescale_colour(grey(4.), 4, &tone_mapper), (255, 255, 255));
+        assert_eq!(rescale_colour(grey(0.25), 1, &tone_mapper), (128, 128, 128));
+    }
+}
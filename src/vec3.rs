@@ -55,6 +55,7 @@ impl Vec3 {
     pub fn random_unit_vector() -> Self {
         Self::random_in_unit_sphere().unit_vector()
     }
+    #[allow(dead_code)]
     pub fn random_in_hemisphere(normal: Self) -> Self {
         let in_unit_sphere = Self::random_in_unit_sphere();
         if dot(in_unit_sphere, normal) > 0. {